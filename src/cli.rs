@@ -1,6 +1,10 @@
 use crate::generation::OutputType;
+use crate::generator::{
+	CsvOptions, LineTerminator, ListFormat, QuoteStyle, SqlDialect, SqlOptions,
+};
+use crate::specification::io::SpecFormat;
 
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, ArgMatches, ErrorKind, SubCommand};
 use serde_derive::{Deserialize, Serialize};
 use std::default::Default;
 use std::path::PathBuf;
@@ -8,11 +12,20 @@ use std::path::PathBuf;
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CliArgs {
 	pub model_name: String,
+	pub plan_path: Option<PathBuf>,
+	pub plan_models: Vec<(String, usize)>,
 	pub gen_spec_path: PathBuf,
+	/// The format of the spec file, where it should not be chosen by the file extension
+	pub spec_format: Option<SpecFormat>,
 	pub output_path: PathBuf,
 	pub output_type: OutputType,
 	pub model_amount: usize,
 	pub pretty_print: bool,
+	pub seed: Option<u64>,
+	pub csv_options: CsvOptions,
+	pub sql_options: SqlOptions,
+	/// Check the spec for problems instead of generating any models
+	pub validate_only: bool,
 }
 
 impl<'s> From<&'s str> for OutputType {
@@ -20,30 +33,72 @@ impl<'s> From<&'s str> for OutputType {
 		match s {
 			"csv" => OutputType::CSV,
 			"json" => OutputType::JSON,
+			"sql" => OutputType::SQL,
+			"ndjson" | "jsonl" => OutputType::NDJSON,
 			_ => OutputType::CSV,
 		}
 	}
 }
 
+impl<'s> From<&'s str> for SpecFormat {
+	fn from(s: &'s str) -> Self {
+		match s {
+			"yaml" => SpecFormat::YAML,
+			"toml" => SpecFormat::TOML,
+			_ => SpecFormat::JSON,
+		}
+	}
+}
+
 impl Default for CliArgs {
 	fn default() -> Self {
 		CliArgs {
 			model_name: String::default(),
+			plan_path: None,
+			plan_models: Vec::new(),
 			gen_spec_path: PathBuf::default(),
+			spec_format: None,
 			output_path: PathBuf::default(),
 			output_type: OutputType::CSV,
 			model_amount: 1,
 			pretty_print: true,
+			seed: None,
+			csv_options: CsvOptions::default(),
+			sql_options: SqlOptions::default(),
+			validate_only: false,
 		}
 	}
 }
 
 pub fn get_args_from_stdin() -> CliArgs {
-	let matches = App::new("mockery.rs")
+	args_from_matches(&app().get_matches()).unwrap_or_else(|e| e.exit())
+}
+
+fn app() -> App<'static, 'static> {
+	App::new("mockery.rs")
         .version(env!("CARGO_PKG_VERSION"))
         .author("Louis Capitanchik <contact@louiscap.co>")
         .about("Generate spec based model data.")
         .after_help("To view help, use -h. For long form help, use --help")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(SubCommand::with_name("validate")
+            .about("Checks a spec for problems without generating any models")
+            .arg(Arg::with_name("spec")
+                .short("s")
+                .long("spec")
+                .help("Sets the spec file to check")
+                .value_name("SPEC_PATH")
+                .long_help("Sets the spec file to check. By default, mockery will look for a 'spec.json' file in CWD. Every problem found is printed with its location in the spec")
+                .required(false))
+            .arg(Arg::with_name("spec_format")
+                .long("spec-format")
+                .help("Sets the format of the spec file")
+                .value_name("FORMAT")
+                .possible_value("json")
+                .possible_value("yaml")
+                .possible_value("toml")
+                .long_help("Sets the format of the spec file. By default, the format is chosen by the file extension: '.yaml' or '.yml' files are read as YAML, '.toml' files as TOML and anything else as JSON")
+                .required(false)))
         .arg(Arg::with_name("spec")
             .short("s")
             .long("spec")
@@ -51,6 +106,15 @@ pub fn get_args_from_stdin() -> CliArgs {
             .value_name("SPEC_PATH")
             .long_help("Sets the spec file to use. By default, mockery will look for a 'spec.json' file in CWD, and will error if it can not be found")
             .required(false))
+        .arg(Arg::with_name("spec_format")
+            .long("spec-format")
+            .help("Sets the format of the spec file")
+            .value_name("FORMAT")
+            .possible_value("json")
+            .possible_value("yaml")
+            .possible_value("toml")
+            .long_help("Sets the format of the spec file. By default, the format is chosen by the file extension: '.yaml' or '.yml' files are read as YAML, '.toml' files as TOML and anything else as JSON")
+            .required(false))
         .arg(Arg::with_name("type")
             .short("t")
             .long("type")
@@ -58,7 +122,10 @@ pub fn get_args_from_stdin() -> CliArgs {
             .value_name("OUTPUT_TYPE")
             .possible_value("csv")
             .possible_value("json")
-            .long_help("Sets the output type. This value defaults to CSV for higher compatibility and throughput")
+            .possible_value("sql")
+            .possible_value("ndjson")
+            .possible_value("jsonl")
+            .long_help("Sets the output type. This value defaults to CSV for higher compatibility and throughput. NDJSON (or JSONL) output writes one JSON object per line, without the surrounding array. SQL output writes numbered files of INSERT statements, which insert parent models before their children when run in name order")
             .required(false))
         .arg(Arg::with_name("amount")
             .short("n")
@@ -72,28 +139,134 @@ pub fn get_args_from_stdin() -> CliArgs {
             .help("Whether or not the output should be formatted for human consumption. Default: false")
             .takes_value(false)
             .required(false))
+        .arg(Arg::with_name("seed")
+            .long("seed")
+            .help("Seeds the random number generator, for repeatable output")
+            .value_name("SEED")
+            .long_help("Seeds the random number generator with the given integer. Generating the same model from the same spec with the same seed and number will always produce identical output files")
+            .required(false))
+        .arg(Arg::with_name("list_format")
+            .long("list-format")
+            .help("Sets how lists of values are written in CSV output")
+            .value_name("LIST_FORMAT")
+            .possible_value("json")
+            .possible_value("postgres")
+            .possible_value("delimited")
+            .long_help("Sets how lists of values are written into a single field of CSV output. This value defaults to json; postgres writes an array literal and delimited joins the values with the list delimiter")
+            .required(false))
+        .arg(Arg::with_name("list_delimiter")
+            .long("list-delimiter")
+            .help("Sets the delimiter used by the delimited list format. Default: ;")
+            .value_name("DELIMITER")
+            .required(false))
+        .arg(Arg::with_name("no_header")
+            .long("no-header")
+            .help("Omits the header row from CSV output")
+            .takes_value(false)
+            .required(false))
+        .arg(Arg::with_name("csv_delimiter")
+            .long("csv-delimiter")
+            .help("Sets the single character that separates fields in CSV output. Default: ,")
+            .value_name("DELIMITER")
+            .required(false))
+        .arg(Arg::with_name("csv_quote")
+            .long("csv-quote")
+            .help("Sets when fields in CSV output are quoted")
+            .value_name("QUOTE_STYLE")
+            .possible_value("necessary")
+            .possible_value("always")
+            .possible_value("non-numeric")
+            .possible_value("never")
+            .long_help("Sets when fields in CSV output are wrapped in quotes. This value defaults to necessary, which only quotes fields containing a delimiter, quote or line break")
+            .required(false))
+        .arg(Arg::with_name("csv_terminator")
+            .long("csv-terminator")
+            .help("Sets the line ending written after each row of CSV output")
+            .value_name("TERMINATOR")
+            .possible_value("lf")
+            .possible_value("crlf")
+            .long_help("Sets the line ending written after each row of CSV output. This value defaults to lf")
+            .required(false))
+        .arg(Arg::with_name("csv_null")
+            .long("csv-null")
+            .help("Sets the text written for null values in CSV output. Default: null")
+            .value_name("TOKEN")
+            .required(false))
+        .arg(Arg::with_name("sql_dialect")
+            .long("sql-dialect")
+            .help("Sets the SQL dialect used to quote values in SQL output")
+            .value_name("DIALECT")
+            .possible_value("postgres")
+            .possible_value("mysql")
+            .possible_value("sqlite")
+            .long_help("Sets the SQL dialect used to quote identifiers and escape values in SQL output. This value defaults to postgres")
+            .required(false))
+        .arg(Arg::with_name("sql_batch_size")
+            .long("sql-batch-size")
+            .help("Sets the maximum number of rows in a single INSERT statement. Default: 500")
+            .value_name("ROWS")
+            .required(false))
+        .arg(Arg::with_name("plan")
+            .long("plan")
+            .help("Sets a run plan file listing several models to generate")
+            .value_name("PLAN_PATH")
+            .long_help("Sets a run plan file, containing a map of model names to quantities under a 'models' key. Every model in the plan is generated into the same output folder. When a plan is used, the MODEL argument is omitted")
+            .required(false))
+        .arg(Arg::with_name("models")
+            .short("m")
+            .long("model")
+            .help("Adds a model to generate, with the number of root models to generate for it")
+            .value_name("NAME=NUMBER")
+            .long_help("Adds a model to generate, with the number of root models to generate for it. Can be repeated to generate several models into the same output folder, and overrides the quantity of a model in the run plan. When this is used, the MODEL argument is omitted")
+            .multiple(true)
+            .number_of_values(1)
+            .validator(|value| parse_plan_model(&value).map(|_| ()))
+            .required(false))
         .arg(Arg::with_name("MODEL")
             .help("Sets the model to generate")
             .long_help("Sets the model to generate. The model determines what files will be generated based on it's definition in the spec")
-            .required(true)
+            .required_unless_one(&["plan", "models"])
             .index(1))
         .arg(Arg::with_name("OUTPUT")
             .help("Sets the output path. Must be a file path pointing to a folder that optionally exists")
             .long_help("Sets the output path. Must be a file path pointing to a folder that optionally exists; if it does not exist, it will be created. Files corresponding to the input model names will be created inside this folder")
-            .required(false)
+            .required_unless_one(&["plan", "models"])
             .index(2))
-        .get_matches();
+}
+
+fn args_from_matches(matches: &ArgMatches) -> Result<CliArgs, clap::Error> {
+	let validate_matches = matches.subcommand_matches("validate");
+	let is_planned = matches.is_present("plan") || matches.is_present("models");
+	// Without a model name, the only positional argument given is the output path
+	let (model_name, output_path) = if is_planned && !matches.is_present("OUTPUT") {
+		(None, matches.value_of("MODEL"))
+	} else {
+		(matches.value_of("MODEL"), matches.value_of("OUTPUT"))
+	};
+	if output_path.is_none() && validate_matches.is_none() {
+		return Err(clap::Error::with_description(
+			"The OUTPUT argument is required",
+			ErrorKind::MissingRequiredArgument,
+		));
+	}
 
-	CliArgs {
-		model_name: matches.value_of("MODEL").map(|s| String::from(s)).unwrap(),
-		gen_spec_path: matches
-			.value_of("spec")
+	Ok(CliArgs {
+		model_name: model_name.map(|s| String::from(s)).unwrap_or_default(),
+		plan_path: matches.value_of("plan").map(|s| PathBuf::from(s)),
+		plan_models: matches
+			.values_of("models")
+			.map(|values| values.filter_map(|v| parse_plan_model(v).ok()).collect())
+			.unwrap_or_default(),
+		gen_spec_path: validate_matches
+			.and_then(|m| m.value_of("spec"))
+			.or_else(|| matches.value_of("spec"))
 			.map(|s| PathBuf::from(s))
 			.unwrap_or_else(|| PathBuf::from("spec.json")),
-		output_path: matches
-			.value_of("OUTPUT")
-			.map(|s| PathBuf::from(s))
-			.unwrap(),
+		spec_format: validate_matches
+			.and_then(|m| m.value_of("spec_format"))
+			.or_else(|| matches.value_of("spec_format"))
+			.map(|s| SpecFormat::from(s)),
+		output_path: output_path.map(|s| PathBuf::from(s)).unwrap_or_default(),
 		output_type: matches
 			.value_of("type")
 			.map(|s| OutputType::from(s))
@@ -103,5 +276,109 @@ pub fn get_args_from_stdin() -> CliArgs {
 			.map(|s| s.parse::<usize>().unwrap())
 			.unwrap_or(1),
 		pretty_print: matches.is_present("pretty"),
+		seed: matches.value_of("seed").map(|s| {
+			s.parse::<u64>()
+				.expect("The seed must be a positive integer")
+		}),
+		csv_options: CsvOptions {
+			list_format: match matches.value_of("list_format") {
+				Some("postgres") => ListFormat::Postgres,
+				Some("delimited") => ListFormat::Delimited(
+					matches
+						.value_of("list_delimiter")
+						.map(String::from)
+						.unwrap_or_else(|| String::from(";")),
+				),
+				_ => ListFormat::JSON,
+			},
+			header: !matches.is_present("no_header"),
+			delimiter: matches
+				.value_of("csv_delimiter")
+				.map(|s| {
+					if s.len() != 1 {
+						panic!("The CSV delimiter must be a single character");
+					}
+					s.as_bytes()[0]
+				})
+				.unwrap_or(b','),
+			quote_style: match matches.value_of("csv_quote") {
+				Some("always") => QuoteStyle::Always,
+				Some("non-numeric") => QuoteStyle::NonNumeric,
+				Some("never") => QuoteStyle::Never,
+				_ => QuoteStyle::Necessary,
+			},
+			line_terminator: match matches.value_of("csv_terminator") {
+				Some("crlf") => LineTerminator::CRLF,
+				_ => LineTerminator::LF,
+			},
+			null_value: matches
+				.value_of("csv_null")
+				.map(String::from)
+				.unwrap_or_else(|| String::from("null")),
+		},
+		sql_options: SqlOptions {
+			dialect: match matches.value_of("sql_dialect") {
+				Some("mysql") => SqlDialect::MySQL,
+				Some("sqlite") => SqlDialect::SQLite,
+				_ => SqlDialect::Postgres,
+			},
+			batch_size: matches
+				.value_of("sql_batch_size")
+				.map(|s| {
+					s.parse::<usize>()
+						.expect("The SQL batch size must be a positive integer")
+				})
+				.unwrap_or_else(|| SqlOptions::default().batch_size),
+		},
+		validate_only: validate_matches.is_some(),
+	})
+}
+
+/// Parse a `--model` value in the form `name=number` into its parts
+fn parse_plan_model(value: &str) -> Result<(String, usize), String> {
+	let mut parts = value.splitn(2, '=');
+	let name = parts.next().unwrap_or_default();
+	let quantity = parts
+		.next()
+		.ok_or_else(|| String::from("Models must be given in the form name=number"))?;
+	if name.is_empty() {
+		return Err(String::from("Models must be given in the form name=number"));
 	}
+	let quantity = quantity
+		.parse::<usize>()
+		.map_err(|_| String::from("The model number must be a positive integer"))?;
+	Ok((String::from(name), quantity))
+}
+
+#[test]
+fn the_output_path_is_required_in_every_form() {
+	let parse = |args: &[&str]| {
+		app()
+			.get_matches_from_safe(args)
+			.and_then(|matches| args_from_matches(&matches))
+	};
+
+	let args = parse(&["mockery", "user", "out"]).unwrap();
+	assert_eq!(args.model_name, "user");
+	assert_eq!(args.output_path, PathBuf::from("out"));
+
+	let args = parse(&["mockery", "-m", "user=2", "out"]).unwrap();
+	assert_eq!(args.model_name, "");
+	assert_eq!(args.output_path, PathBuf::from("out"));
+	assert_eq!(args.plan_models, vec![(String::from("user"), 2)]);
+
+	let args = parse(&["mockery", "--plan", "plan.json", "post", "out"]).unwrap();
+	assert_eq!(args.model_name, "post");
+	assert_eq!(args.output_path, PathBuf::from("out"));
+
+	assert!(parse(&["mockery", "validate"]).unwrap().validate_only);
+
+	let err = parse(&["mockery", "user"]).unwrap_err();
+	assert_eq!(err.kind, ErrorKind::MissingRequiredArgument);
+	let err = parse(&["mockery", "--plan", "plan.json"]).unwrap_err();
+	assert_eq!(err.kind, ErrorKind::MissingRequiredArgument);
+	let err = parse(&["mockery", "-m", "user", "out"]).unwrap_err();
+	assert_eq!(err.kind, ErrorKind::ValueValidation);
+	let err = parse(&["mockery", "-m", "user=many", "out"]).unwrap_err();
+	assert_eq!(err.kind, ErrorKind::ValueValidation);
 }
@@ -1,7 +1,16 @@
+use chrono::format::{DelayedFormat, Item, StrftimeItems};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveTime, TimeZone, Timelike, Utc};
 use fake::faker;
 use fake::{Fake, Faker};
+use rand::distributions::{Distribution, WeightedIndex};
 use rand::Rng;
+use rand_distr::{Exp, LogNormal, Normal, Poisson};
+use regex_syntax::hir::{self, Hir, HirKind, RepetitionKind, RepetitionRange};
 use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::ops::Add;
 use uuid;
 
@@ -73,12 +82,57 @@ pub enum RandomData {
 	///
 	/// ## Examples
 	///
-	/// `NumberBetween: { "min": 23, "max": 50 }` will generate a number between 23 and 49, inclusive
+	/// `NumberBetween: { "min": 23, "max": 50 }` will generate a number between 23 and 49, inclusive.
+	/// `NumberBetween: { "min": -10, "max": 10 }` will generate a number between -10 and 9
 	NumberBetween {
 		/// The minimum boundary for the generated number. This boundary is inclusive
-		min: usize,
+		min: i64,
 		/// The maximum boundary for the generated number. This boundary is exclusive
-		max: usize,
+		max: i64,
+		/// The shape of the spread of generated numbers. Defaults to `Uniform`
+		distribution: Option<NumberDistribution>,
+	},
+	/// Generate a random decimal number between the minimum and maximum boundaries
+	///
+	/// ## Examples
+	///
+	/// `Float: { "min": -20.0, "max": 40.0, "precision": 1 }` will generate a temperature such as
+	/// `21.4`
+	Float {
+		/// The minimum boundary for the generated number. This boundary is inclusive
+		min: f64,
+		/// The maximum boundary for the generated number. This boundary is exclusive
+		max: f64,
+		/// The number of decimal places to round the generated number to. Defaults to no rounding
+		precision: Option<u32>,
+		/// The shape of the spread of generated numbers. Defaults to `Uniform`
+		distribution: Option<NumberDistribution>,
+	},
+	/// Generate a random decimal number between the minimum and maximum boundaries, with exactly
+	/// `precision` decimal places. The number is generated as a string so that trailing zeros are
+	/// kept and no floating point error is introduced, which suits prices and balances
+	///
+	/// ## Examples
+	///
+	/// `Decimal: { "min": 0, "max": 100 }` will generate a price such as `"42.50"`
+	Decimal {
+		/// The minimum boundary for the generated number. This boundary is inclusive
+		min: f64,
+		/// The maximum boundary for the generated number. This boundary is exclusive
+		max: f64,
+		/// The number of decimal places in the generated number. Defaults to 2
+		precision: Option<u32>,
+		/// The shape of the spread of generated numbers. Defaults to `Uniform`
+		distribution: Option<NumberDistribution>,
+	},
+	/// Generate `true` or `false`
+	///
+	/// ## Examples
+	///
+	/// `Boolean: { "probability": 0.9 }` will generate `true` for roughly 90% of instances
+	Boolean {
+		/// The chance of generating `true`, between 0 and 1. Defaults to 0.5
+		probability: Option<f64>,
 	},
 	/// Generate a number of paragraphs containing raw lorem ipsum text (No formatting)
 	Paragraphs {
@@ -106,9 +160,9 @@ pub enum RandomData {
 	Latitude,
 	/// Generates a valid longitude component
 	Longitude,
-	/// Generates a coordinate pair, formatted as a JSON array of (Latitude, Longitude)
+	/// Generates a coordinate pair as an array of (Latitude, Longitude)
 	LatLong,
-	/// Generates a coordinate pair, formatted as a JSON array of (Longitude, Latitude)
+	/// Generates a coordinate pair as an array of (Longitude, Latitude)
 	LongLat,
 	/// Generates a GeoJson point object using the WKT formatting for postgis recognition and insertion
 	GeoPoint,
@@ -128,6 +182,108 @@ pub enum RandomData {
 		height: Option<usize>,
 		grayscale: Option<bool>,
 	},
+	/// Generates a calendar date between the optional `after` and `before` boundaries, written as
+	/// `YYYY-MM-DD` dates. Where absent, the boundaries default to `2000-01-01` and `2030-01-01`.
+	///
+	/// ## Examples
+	///
+	/// `Date: { "after": "2019-01-01", "format": "%d/%m/%Y" }` will generate a date in or after
+	/// 2019, formatted as `17/10/2021`
+	Date {
+		/// The earliest date that can be generated. This boundary is inclusive
+		after: Option<NaiveDate>,
+		/// The latest date that can be generated. This boundary is exclusive
+		before: Option<NaiveDate>,
+		/// A strftime style format string. Defaults to `%Y-%m-%d`
+		format: Option<String>,
+	},
+	/// Generates a time of day between the optional `after` and `before` boundaries, written as
+	/// `HH:MM:SS` times. Where absent, any time of day can be generated.
+	///
+	/// ## Examples
+	///
+	/// `Time: { "after": "09:00:00", "before": "17:30:00", "format": "%H:%M" }` will generate a
+	/// time during office hours, without the seconds component
+	Time {
+		/// The earliest time that can be generated. This boundary is inclusive
+		after: Option<NaiveTime>,
+		/// The latest time that can be generated. This boundary is exclusive
+		before: Option<NaiveTime>,
+		/// A strftime style format string. Defaults to `%H:%M:%S`
+		format: Option<String>,
+	},
+	/// Generates a date and time between the optional `after` and `before` boundaries, written as
+	/// RFC 3339 timestamps. Where absent, the boundaries default to `2000-01-01T00:00:00Z` and
+	/// `2030-01-01T00:00:00Z`.
+	///
+	/// ## Examples
+	///
+	/// `DateTime: { "after": "2019-06-01T00:00:00Z", "timezone": "+02:00" }` will generate an
+	/// RFC 3339 timestamp after June 2019, expressed with a two hour offset from UTC
+	DateTime {
+		/// The earliest date and time that can be generated. This boundary is inclusive
+		after: Option<DateTime<FixedOffset>>,
+		/// The latest date and time that can be generated. This boundary is exclusive
+		before: Option<DateTime<FixedOffset>>,
+		/// A strftime style format string. Defaults to RFC 3339
+		format: Option<String>,
+		/// The UTC offset that the generated value is expressed in, such as `+05:30` or `UTC`.
+		/// Defaults to UTC
+		timezone: Option<Timezone>,
+	},
+	/// Generates the number of seconds since the unix epoch for a date and time between the
+	/// optional `after` and `before` boundaries. Uses the same defaults as `DateTime`
+	UnixTimestamp {
+		/// The earliest date and time that can be generated. This boundary is inclusive
+		after: Option<DateTime<FixedOffset>>,
+		/// The latest date and time that can be generated. This boundary is exclusive
+		before: Option<DateTime<FixedOffset>>,
+	},
+	/// Picks one of the given values. Each value is equally likely to be picked, unless `weights`
+	/// are given.
+	///
+	/// ## Examples
+	///
+	/// `OneOf: { "values": ["active", "suspended", "deleted"], "weights": [8, 1, 1] }` will
+	/// generate `active` for roughly 80% of instances
+	OneOf {
+		/// The values to pick from, which can be any JSON value
+		values: Vec<Value>,
+		/// The relative likelihood of picking each value, in the same order as `values`
+		weights: Option<Vec<f64>>,
+	},
+	/// Picks a random subset of the given values, generated as an array in the same order as the
+	/// values are listed.
+	///
+	/// ## Examples
+	///
+	/// `ManyOf: { "values": ["admin", "editor", "viewer"], "min": 1 }` will generate between one
+	/// and three of the roles
+	ManyOf {
+		/// The values to pick from, which can be any JSON value
+		values: Vec<Value>,
+		/// The relative likelihood of picking each value, in the same order as `values`
+		weights: Option<Vec<f64>>,
+		/// The minimum number of values to pick. This boundary is inclusive and defaults to 0
+		min: Option<usize>,
+		/// The maximum number of values to pick. This boundary is inclusive and defaults to the
+		/// number of values
+		max: Option<usize>,
+	},
+	/// Generates a string that matches the given regular expression. Repetitions without an upper
+	/// bound, such as `*` and `+`, are limited to `max_repeat` times. Characters are drawn from
+	/// printable ASCII wherever the pattern allows it
+	///
+	/// ## Examples
+	///
+	/// `Pattern: { "regex": "ACC-[0-9]{8}" }` will generate values like `ACC-40718263`
+	Pattern {
+		/// The regular expression that generated values will match. Anchors are only supported
+		/// at the start and end of the pattern, and word boundaries are not supported
+		regex: String,
+		/// The most times that an unbounded repetition will be generated. Defaults to 8
+		max_repeat: Option<u32>,
+	},
 	NullValue,
 	String {
 		content: String,
@@ -141,17 +297,841 @@ pub enum RandomData {
 impl RandomData {
 	/// Consumes the `RandomData` instance and turns it into a random piece of data, corresponding to
 	/// its type
-	pub fn into_data(self) -> String {
+	pub fn into_data(self) -> Value {
 		generate_fake_data(self)
 	}
+
+	/// Check that the options of this `RandomData` can be used to generate data, describing the
+	/// problem where they can not
+	pub fn validate(&self) -> Result<(), String> {
+		match self {
+			RandomData::Number { digits: 0 } => {
+				Err(String::from("Number must have at least 1 digit"))
+			}
+			RandomData::NumberBetween { min, max, .. } if min >= max => Err(format!(
+				"NumberBetween min ({}) must be less than max ({})",
+				min, max
+			)),
+			RandomData::NumberBetween { distribution, .. } => check_distribution(distribution),
+			RandomData::Float {
+				min,
+				max,
+				precision,
+				distribution,
+			} => {
+				check_range("Float", *min, *max)?;
+				if let Some(precision) = precision {
+					check_precision("Float", *min, *max, *precision)?;
+				}
+				check_distribution(distribution)
+			}
+			RandomData::Decimal {
+				min,
+				max,
+				precision,
+				distribution,
+			} => {
+				let precision = precision.unwrap_or(DEFAULT_DECIMAL_PRECISION);
+				check_range("Decimal", *min, *max)?;
+				check_precision("Decimal", *min, *max, precision)?;
+				check_distribution(distribution)?;
+				let scale = 10f64.powi(precision as i32);
+				if min.abs().max(max.abs()) * scale > MAX_EXACT_INTEGER {
+					Err(String::from(
+						"Decimal min and max are too large to be generated with this precision",
+					))
+				} else {
+					Ok(())
+				}
+			}
+			RandomData::Boolean {
+				probability: Some(probability),
+			} if *probability < 0.0 || *probability > 1.0 => Err(format!(
+				"Boolean probability ({}) must be between 0 and 1",
+				probability
+			)),
+			RandomData::Date {
+				after,
+				before,
+				format,
+			} => {
+				let after = after.unwrap_or_else(|| default_after().naive_utc().date());
+				let before = before.unwrap_or_else(|| default_before().naive_utc().date());
+				check_bounds("Date", after, before)?;
+				check_format("Date", format, |format| after.format(format))
+			}
+			RandomData::Time {
+				after,
+				before,
+				format,
+			} => {
+				// Times are generated to the second, and a missing `before` is the end of the day,
+				// which every time is earlier than
+				let whole_seconds = |time: NaiveTime| {
+					NaiveTime::from_num_seconds_from_midnight(time.num_seconds_from_midnight(), 0)
+				};
+				let after = whole_seconds(after.unwrap_or_else(|| NaiveTime::from_hms(0, 0, 0)));
+				if let Some(before) = before {
+					check_bounds("Time", after, whole_seconds(*before))?;
+				}
+				check_format("Time", format, |format| after.format(format))
+			}
+			RandomData::DateTime {
+				after,
+				before,
+				format,
+				..
+			} => {
+				let after = after.map_or_else(default_after, |a| a.with_timezone(&Utc));
+				let before = before.map_or_else(default_before, |b| b.with_timezone(&Utc));
+				check_bounds("DateTime", after, before)?;
+				let sample = after.with_timezone(&FixedOffset::east(0));
+				check_format("DateTime", format, |format| sample.format(format))
+			}
+			RandomData::UnixTimestamp { after, before } => check_bounds(
+				"UnixTimestamp",
+				after.map_or_else(default_after, |a| a.with_timezone(&Utc)),
+				before.map_or_else(default_before, |b| b.with_timezone(&Utc)),
+			),
+			RandomData::OneOf { values, weights } => check_choices("OneOf", values, weights),
+			RandomData::ManyOf {
+				values,
+				weights,
+				min,
+				max,
+			} => {
+				check_choices("ManyOf", values, weights)?;
+				let min = min.unwrap_or(0);
+				if min > values.len() {
+					return Err(format!(
+						"ManyOf min ({}) must not be greater than the number of values ({})",
+						min,
+						values.len()
+					));
+				}
+				// Values with a weight of 0 are never picked, so they can not make up the minimum
+				if let Some(weights) = weights {
+					let weighted = weights.iter().filter(|weight| **weight > 0.0).count();
+					if min > weighted {
+						return Err(format!(
+							"ManyOf min ({}) must not be greater than the number of values with a weight above 0 ({})",
+							min, weighted
+						));
+					}
+				}
+				match max {
+					Some(max) if *max < min => Err(format!(
+						"ManyOf min ({}) must not be greater than max ({})",
+						min, max
+					)),
+					_ => Ok(()),
+				}
+			}
+			RandomData::Pattern { regex, .. } => parse_pattern(regex).map(|_| ()),
+			_ => Ok(()),
+		}
+	}
+
+	/// Consumes the `RandomData` instance and turns it into a random piece of data, drawing all of
+	/// its randomness from the given source. Seeding that source will produce repeatable data
+	pub fn into_data_with_rng<R: Rng + ?Sized>(self, rng: &mut R) -> Value {
+		generate_fake_data_with_rng(self, rng)
+	}
+
+	/// Consumes the `RandomData` instance and turns it into a random piece of data for the given
+	/// locale, drawing all of its randomness from the given source
+	pub fn into_localized_data_with_rng<R: Rng + ?Sized>(
+		self,
+		locale: Locale,
+		rng: &mut R,
+	) -> Value {
+		generate_localized_data_with_rng(self, locale, rng)
+	}
+
+	/// Check that this `RandomData` can be generated for the given locale. Types that do not
+	/// depend on a locale, such as numbers and dates, can be generated for any of them
+	pub fn check_locale(&self, locale: Locale) -> Result<(), String> {
+		let supported = match self {
+			RandomData::Email => match locale {
+				Locale::EN | Locale::FR_FR | Locale::PT_BR => true,
+				_ => false,
+			},
+			RandomData::Company => match locale {
+				Locale::EN | Locale::JA_JP | Locale::PT_BR => true,
+				_ => false,
+			},
+			RandomData::City | RandomData::FullAddress => locale == Locale::EN,
+			RandomData::StreetAddress | RandomData::Postcode => match locale {
+				Locale::EN | Locale::PT_BR => true,
+				_ => false,
+			},
+			RandomData::PhoneNumber => match locale {
+				Locale::EN | Locale::FR_FR | Locale::JA_JP | Locale::PT_BR => true,
+				_ => false,
+			},
+			_ => true,
+		};
+		if supported {
+			Ok(())
+		} else {
+			Err(format!(
+				"{:?} is not available in the `{}` locale",
+				self, locale
+			))
+		}
+	}
+}
+
+/// The language and region that names, addresses and other text are generated for. These are all
+/// of the locales provided by the `fake` crate, written in a specification as `en`, `fr_FR`,
+/// `zh_TW`, `zh_CN`, `ar_SA`, `ja_JP` or `pt_BR`
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Locale {
+	/// English, which every type can be generated in
+	#[serde(rename = "en")]
+	EN,
+	/// French, as used in France. Names, emails and phone numbers are available
+	#[serde(rename = "fr_FR")]
+	FR_FR,
+	/// Traditional Chinese, as used in Taiwan. Only names are available
+	#[serde(rename = "zh_TW")]
+	ZH_TW,
+	/// Simplified Chinese, as used in China. Only names are available
+	#[serde(rename = "zh_CN")]
+	ZH_CN,
+	/// Arabic, as used in Saudi Arabia. Only names are available
+	#[serde(rename = "ar_SA")]
+	AR_SA,
+	/// Japanese, as used in Japan. Names, companies and phone numbers are available
+	#[serde(rename = "ja_JP")]
+	JA_JP,
+	/// Portuguese, as used in Brazil. Everything but cities and full addresses is available
+	#[serde(rename = "pt_BR")]
+	PT_BR,
+}
+
+impl Default for Locale {
+	fn default() -> Self {
+		Locale::EN
+	}
+}
+
+impl std::fmt::Display for Locale {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Locale::EN => write!(f, "en"),
+			Locale::FR_FR => write!(f, "fr_FR"),
+			Locale::ZH_TW => write!(f, "zh_TW"),
+			Locale::ZH_CN => write!(f, "zh_CN"),
+			Locale::AR_SA => write!(f, "ar_SA"),
+			Locale::JA_JP => write!(f, "ja_JP"),
+			Locale::PT_BR => write!(f, "pt_BR"),
+		}
+	}
 }
 
 impl std::string::ToString for RandomData {
 	fn to_string(&self) -> String {
-		self.clone().into_data()
+		plain_text(&self.clone().into_data())
+	}
+}
+
+/// A fixed offset from UTC, written in a specification as `UTC`, `Z` or a signed offset in the form
+/// `+HH:MM`, `+HHMM` or `+HH`
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Timezone(FixedOffset);
+
+impl TryFrom<String> for Timezone {
+	type Error = String;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		let invalid = || {
+			format!(
+				"Invalid timezone {}, expected UTC or an offset such as +01:00",
+				value
+			)
+		};
+		let trimmed = value.trim();
+		if trimmed.eq_ignore_ascii_case("utc") || trimmed == "Z" {
+			return Ok(Timezone(FixedOffset::east(0)));
+		}
+
+		let sign = match trimmed.chars().next() {
+			Some('+') => 1,
+			Some('-') => -1,
+			_ => return Err(invalid()),
+		};
+		let digits: String = trimmed[1..].chars().filter(|c| *c != ':').collect();
+		if (digits.len() != 2 && digits.len() != 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+			return Err(invalid());
+		}
+		let hours = digits[0..2].parse::<i32>().map_err(|_| invalid())?;
+		let minutes = if digits.len() == 4 {
+			digits[2..4].parse::<i32>().map_err(|_| invalid())?
+		} else {
+			0
+		};
+
+		FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+			.map(Timezone)
+			.ok_or_else(invalid)
+	}
+}
+
+impl From<Timezone> for String {
+	fn from(timezone: Timezone) -> Self {
+		timezone.0.to_string()
+	}
+}
+
+/// Check that a strftime style format string can write the sample value, which has the same fields
+/// as the values that are generated with it. Unknown specifiers, and specifiers for fields that the
+/// value does not have, would otherwise panic once a value is written
+fn check_format<'a, F>(name: &str, format: &'a Option<String>, write: F) -> Result<(), String>
+where
+	F: Fn(&'a str) -> DelayedFormat<StrftimeItems<'a>>,
+{
+	let format = match format {
+		Some(format) => format,
+		None => return Ok(()),
+	};
+	if StrftimeItems::new(format).any(|item| item == Item::Error) {
+		return Err(format!(
+			"{} format ({}) is not a valid strftime format",
+			name, format
+		));
+	}
+	let mut text = String::new();
+	match std::fmt::Write::write_fmt(&mut text, format_args!("{}", write(format))) {
+		Ok(()) => Ok(()),
+		Err(_) => Err(format!(
+			"{} format ({}) uses fields that a {} does not have",
+			name, format, name
+		)),
+	}
+}
+
+fn check_range(name: &str, min: f64, max: f64) -> Result<(), String> {
+	if min < max {
+		Ok(())
+	} else {
+		Err(format!(
+			"{} min ({}) must be less than max ({})",
+			name, min, max
+		))
+	}
+}
+
+/// The smallest value that is at least `min`, and the smallest value that is at least `max`, with
+/// the given number of decimal places. Both are multiplied by `10^precision`, so that they are whole
+fn scaled_bounds(min: f64, max: f64, precision: u32) -> (f64, f64) {
+	let scale = 10f64.powi(precision as i32);
+	((min * scale).ceil(), (max * scale).ceil())
+}
+
+fn check_precision(name: &str, min: f64, max: f64, precision: u32) -> Result<(), String> {
+	let (lower, upper) = scaled_bounds(min, max, precision);
+	if lower < upper {
+		Ok(())
+	} else {
+		Err(format!(
+			"{} min ({}) and max ({}) have no value with {} decimal places between them",
+			name, min, max, precision
+		))
+	}
+}
+
+fn check_bounds<T: PartialOrd + std::fmt::Display>(
+	name: &str,
+	after: T,
+	before: T,
+) -> Result<(), String> {
+	if after < before {
+		Ok(())
+	} else {
+		Err(format!(
+			"{} after ({}) must be earlier than before ({})",
+			name, after, before
+		))
+	}
+}
+
+/// The shape of the spread of numbers generated between a minimum and maximum boundary. Numbers
+/// that would fall outside of the boundaries are drawn again, and are clamped to the boundaries if
+/// that keeps happening.
+///
+/// `Normal` and `LogNormal` describe the generated numbers themselves, while `Exponential`,
+/// `Poisson` and `Zipf` describe how far above the minimum each number is.
+///
+/// ## Examples
+///
+/// `NumberBetween: { "min": 18, "max": 100, "distribution": { "Normal": { "mean": 35, "std_dev": 12 } } }`
+/// will generate ages that are mostly between 23 and 47
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum NumberDistribution {
+	/// Every number between the boundaries is equally likely
+	Uniform,
+	/// A bell curve around the `mean`, such as ages or heights
+	Normal { mean: f64, std_dev: f64 },
+	/// A distribution whose logarithm is normal with the given `mean` and `std_dev`, such as order
+	/// values or incomes, where most numbers are small and a few are very large
+	LogNormal { mean: f64, std_dev: f64 },
+	/// The time between independent events, with `rate` events per unit
+	Exponential { rate: f64 },
+	/// The number of independent events in an interval, averaging `mean` events
+	Poisson { mean: f64 },
+	/// Ranked popularity, where the minimum is the most common number, the next number is
+	/// `2^exponent` times less common, and so on
+	Zipf { exponent: f64 },
+}
+
+impl Default for NumberDistribution {
+	fn default() -> Self {
+		NumberDistribution::Uniform
+	}
+}
+
+/// The number of times a number outside of the boundaries is drawn again before it is clamped
+const MAX_DISTRIBUTION_ATTEMPTS: usize = 100;
+
+impl NumberDistribution {
+	/// Draw a number from this distribution that is at least `min` and less than `max`
+	pub fn sample_between<R: Rng + ?Sized>(&self, rng: &mut R, min: f64, max: f64) -> f64 {
+		if max <= min {
+			return min;
+		}
+
+		let mut value = min;
+		for _ in 0..MAX_DISTRIBUTION_ATTEMPTS {
+			let sample = match self {
+				NumberDistribution::Uniform => return rng.gen_range(min..max),
+				NumberDistribution::Normal { mean, std_dev } => {
+					Normal::new(*mean, *std_dev).ok().map(|d| d.sample(rng))
+				}
+				NumberDistribution::LogNormal { mean, std_dev } => {
+					LogNormal::new(*mean, *std_dev).ok().map(|d| d.sample(rng))
+				}
+				NumberDistribution::Exponential { rate } => {
+					Exp::new(*rate).ok().map(|d| min + d.sample(rng))
+				}
+				NumberDistribution::Poisson { mean } => Poisson::new(*mean)
+					.ok()
+					.map(|d| min + Distribution::<f64>::sample(&d, rng)),
+				NumberDistribution::Zipf { exponent } => {
+					Some(min + zipf_rank(rng, (max - min).ceil(), *exponent) - 1.0)
+				}
+			};
+			value = match sample {
+				Some(sample) => sample,
+				None => return rng.gen_range(min..max),
+			};
+			if value >= min && value < max {
+				return value;
+			}
+		}
+		value.max(min).min(largest_below(max))
+	}
+
+	/// Check that the parameters of this distribution can be used to generate numbers
+	pub fn validate(&self) -> Result<(), String> {
+		let (name, value) = match self {
+			NumberDistribution::Uniform => return Ok(()),
+			NumberDistribution::Normal { std_dev, .. } => ("Normal std_dev", std_dev),
+			NumberDistribution::LogNormal { std_dev, .. } => ("LogNormal std_dev", std_dev),
+			NumberDistribution::Exponential { rate } => ("Exponential rate", rate),
+			NumberDistribution::Poisson { mean } => ("Poisson mean", mean),
+			NumberDistribution::Zipf { exponent } => ("Zipf exponent", exponent),
+		};
+		if value.is_finite() && *value > 0.0 {
+			Ok(())
+		} else {
+			Err(format!("{} ({}) must be greater than 0", name, value))
+		}
 	}
 }
 
+/// The largest number that is less than `value`
+fn largest_below(value: f64) -> f64 {
+	let bits = value.to_bits();
+	if value > 0.0 {
+		f64::from_bits(bits - 1)
+	} else if value == 0.0 {
+		-f64::from_bits(1)
+	} else {
+		f64::from_bits(bits + 1)
+	}
+}
+
+/// Draw a rank between 1 and `count` from a Zipf distribution, using Hörmann and Derflinger's
+/// rejection-inversion method so that large ranges do not need to be summed
+fn zipf_rank<R: Rng + ?Sized>(rng: &mut R, count: f64, exponent: f64) -> f64 {
+	let count = count.max(1.0);
+	let q = if exponent != 1.0 {
+		1.0 / (1.0 - exponent)
+	} else {
+		0.0
+	};
+	let t = if exponent != 1.0 {
+		(count.powf(1.0 - exponent) - exponent) * q
+	} else {
+		1.0 + count.ln()
+	};
+	let inverse_cdf = |p: f64| {
+		let pt = p * t;
+		if pt <= 1.0 {
+			pt
+		} else if exponent != 1.0 {
+			(pt * (1.0 - exponent) + exponent).powf(q)
+		} else {
+			(pt - 1.0).exp()
+		}
+	};
+
+	loop {
+		let inverse = inverse_cdf(rng.gen::<f64>());
+		let rank = (inverse + 1.0).floor();
+		let mut ratio = rank.powf(-exponent);
+		if rank > 1.0 {
+			ratio *= inverse.powf(exponent);
+		}
+		if rng.gen::<f64>() < ratio {
+			return rank;
+		}
+	}
+}
+
+fn check_distribution(distribution: &Option<NumberDistribution>) -> Result<(), String> {
+	distribution
+		.as_ref()
+		.map_or(Ok(()), NumberDistribution::validate)
+}
+
+/// The number of decimal places generated by a `Decimal` that does not specify a precision
+const DEFAULT_DECIMAL_PRECISION: u32 = 2;
+
+/// The largest integer that an `f64` can hold exactly
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Generate a number between the two boundaries with the given number of decimal places, written
+/// out in full so that trailing zeros are kept
+fn decimal_between<R: Rng + ?Sized>(
+	rng: &mut R,
+	min: f64,
+	max: f64,
+	precision: u32,
+	distribution: &NumberDistribution,
+) -> String {
+	let scale = 10f64.powi(precision as i32);
+	let (lower, upper) = scaled_bounds(min, max, precision);
+	let (lower, upper) = (lower as i64, upper as i64);
+	let scaled = if upper > lower {
+		let value = distribution.sample_between(rng, min, max);
+		((value * scale).floor() as i64).max(lower).min(upper - 1)
+	} else {
+		lower
+	};
+
+	let sign = if scaled < 0 { "-" } else { "" };
+	let digits = scaled.abs().to_string();
+	let precision = precision as usize;
+	if precision == 0 {
+		return format!("{}{}", sign, digits);
+	}
+	let digits = format!("{:0>width$}", digits, width = precision + 1);
+	let (whole, fraction) = digits.split_at(digits.len() - precision);
+	format!("{}{}.{}", sign, whole, fraction)
+}
+
+fn check_choices(name: &str, values: &[Value], weights: &Option<Vec<f64>>) -> Result<(), String> {
+	if values.is_empty() {
+		return Err(format!("{} must have at least 1 value", name));
+	}
+	match weights {
+		Some(weights) if weights.len() != values.len() => Err(format!(
+			"{} has {} weights for {} values",
+			name,
+			weights.len(),
+			values.len()
+		)),
+		Some(weights) if weights.iter().any(|w| !w.is_finite() || *w < 0.0) => {
+			Err(format!("{} weights must be positive numbers", name))
+		}
+		Some(weights) if weights.iter().all(|w| *w == 0.0) => {
+			Err(format!("{} must have at least 1 weight above 0", name))
+		}
+		_ => Ok(()),
+	}
+}
+
+/// Pick one of the candidate positions in a list of values, using the weight of each candidate
+/// where weights are given. Returns `None` when nothing can be picked
+fn pick_position<R: Rng + ?Sized>(
+	rng: &mut R,
+	candidates: &[usize],
+	weights: &Option<Vec<f64>>,
+) -> Option<usize> {
+	if candidates.is_empty() {
+		return None;
+	}
+	match weights {
+		Some(weights) => {
+			let candidate_weights = candidates
+				.iter()
+				.map(|index| weights.get(*index).cloned().unwrap_or(0.0));
+			WeightedIndex::new(candidate_weights)
+				.ok()
+				.map(|distribution| distribution.sample(rng))
+		}
+		None => Some(rng.gen_range(0..candidates.len())),
+	}
+}
+
+/// The most times that a `Pattern` repeats an unbounded repetition when no `max_repeat` is given
+const DEFAULT_MAX_REPEAT: u32 = 8;
+
+thread_local! {
+	/// Parsed `Pattern` expressions, so that each regex is only parsed once per thread
+	static PATTERNS: RefCell<HashMap<String, Hir>> = RefCell::new(HashMap::new());
+}
+
+/// Parse a `Pattern` regex, describing why it can not be used to generate data where it fails
+fn parse_pattern(regex: &str) -> Result<Hir, String> {
+	let hir = regex_syntax::Parser::new().parse(regex).map_err(|err| {
+		let reason = match &err {
+			regex_syntax::Error::Parse(err) => err.kind().to_string(),
+			regex_syntax::Error::Translate(err) => err.kind().to_string(),
+			err => err.to_string(),
+		};
+		format!("Pattern regex `{}` is not valid: {}", regex, reason)
+	})?;
+
+	// Anchors are satisfied by every generated string when they surround the pattern, but can
+	// not be honoured anywhere else
+	let items = match hir.kind() {
+		HirKind::Concat(items) => items.as_slice(),
+		_ => std::slice::from_ref(&hir),
+	};
+	let leading = items
+		.iter()
+		.take_while(|item| is_anchor(item, true))
+		.count();
+	let trailing = items[leading..]
+		.iter()
+		.rev()
+		.take_while(|item| is_anchor(item, false))
+		.count();
+
+	match items[leading..items.len() - trailing]
+		.iter()
+		.find_map(unsupported_pattern)
+	{
+		Some(reason) => Err(format!(
+			"Pattern regex `{}` is not supported: {}",
+			regex, reason
+		)),
+		None => Ok(hir),
+	}
+}
+
+fn is_anchor(hir: &Hir, start: bool) -> bool {
+	match hir.kind() {
+		HirKind::Anchor(hir::Anchor::StartText) | HirKind::Anchor(hir::Anchor::StartLine) => start,
+		HirKind::Anchor(hir::Anchor::EndText) | HirKind::Anchor(hir::Anchor::EndLine) => !start,
+		_ => false,
+	}
+}
+
+fn unsupported_pattern(hir: &Hir) -> Option<&'static str> {
+	match hir.kind() {
+		HirKind::Anchor(_) => Some("anchors are only supported at the start and end"),
+		HirKind::WordBoundary(_) => Some("word boundaries can not be generated"),
+		HirKind::Class(hir::Class::Unicode(class)) if class.ranges().is_empty() => {
+			Some("character classes must match at least 1 character")
+		}
+		HirKind::Class(hir::Class::Bytes(class)) if class.ranges().is_empty() => {
+			Some("character classes must match at least 1 character")
+		}
+		HirKind::Repetition(repetition) => unsupported_pattern(&repetition.hir),
+		HirKind::Group(group) => unsupported_pattern(&group.hir),
+		HirKind::Concat(items) | HirKind::Alternation(items) => {
+			items.iter().find_map(unsupported_pattern)
+		}
+		_ => None,
+	}
+}
+
+/// Append a random string matching the parsed pattern to `output`
+fn write_pattern<R: Rng + ?Sized>(rng: &mut R, hir: &Hir, max_repeat: u32, output: &mut String) {
+	match hir.kind() {
+		HirKind::Empty | HirKind::Anchor(_) | HirKind::WordBoundary(_) => {}
+		HirKind::Literal(hir::Literal::Unicode(c)) => output.push(*c),
+		HirKind::Literal(hir::Literal::Byte(b)) => output.push(char::from(*b)),
+		HirKind::Class(hir::Class::Unicode(class)) => {
+			let ranges: Vec<(u32, u32)> = class
+				.ranges()
+				.iter()
+				.map(|range| (range.start() as u32, range.end() as u32))
+				.collect();
+			output.extend(pick_char(rng, &ranges));
+		}
+		HirKind::Class(hir::Class::Bytes(class)) => {
+			let ranges: Vec<(u32, u32)> = class
+				.ranges()
+				.iter()
+				.map(|range| (u32::from(range.start()), u32::from(range.end())))
+				.collect();
+			output.extend(pick_char(rng, &ranges));
+		}
+		HirKind::Repetition(repetition) => {
+			let (min, max) = match &repetition.kind {
+				RepetitionKind::ZeroOrOne => (0, 1),
+				RepetitionKind::ZeroOrMore => (0, max_repeat),
+				RepetitionKind::OneOrMore => (1, max_repeat.max(1)),
+				RepetitionKind::Range(RepetitionRange::Exactly(count)) => (*count, *count),
+				RepetitionKind::Range(RepetitionRange::AtLeast(min)) => {
+					(*min, max_repeat.max(*min))
+				}
+				RepetitionKind::Range(RepetitionRange::Bounded(min, max)) => (*min, *max),
+			};
+			for _ in 0..rng.gen_range(min..=max) {
+				write_pattern(rng, &repetition.hir, max_repeat, output);
+			}
+		}
+		HirKind::Group(group) => write_pattern(rng, &group.hir, max_repeat, output),
+		HirKind::Concat(items) => {
+			for item in items {
+				write_pattern(rng, item, max_repeat, output);
+			}
+		}
+		HirKind::Alternation(items) => {
+			let item = &items[rng.gen_range(0..items.len())];
+			write_pattern(rng, item, max_repeat, output);
+		}
+	}
+}
+
+/// Pick a character from the given inclusive ranges of code points, preferring printable ASCII
+/// characters when the ranges include any
+fn pick_char<R: Rng + ?Sized>(rng: &mut R, ranges: &[(u32, u32)]) -> Option<char> {
+	let printable: Vec<(u32, u32)> = ranges
+		.iter()
+		.map(|&(start, end)| (start.max(0x20), end.min(0x7E)))
+		.filter(|(start, end)| start <= end)
+		.collect();
+	let ranges = if printable.is_empty() {
+		ranges
+	} else {
+		&printable
+	};
+
+	let total: u32 = ranges.iter().map(|(start, end)| end - start + 1).sum();
+	if total == 0 {
+		return None;
+	}
+	let mut index = rng.gen_range(0..total);
+	for &(start, end) in ranges {
+		if index <= end - start {
+			// Ranges of characters can span the surrogate code points, which are not characters
+			return std::char::from_u32(start + index).or_else(|| std::char::from_u32(start));
+		}
+		index -= end - start + 1;
+	}
+	None
+}
+
+fn default_after() -> DateTime<Utc> {
+	Utc.ymd(2000, 1, 1).and_hms(0, 0, 0)
+}
+
+fn default_before() -> DateTime<Utc> {
+	Utc.ymd(2030, 1, 1).and_hms(0, 0, 0)
+}
+
+/// Pick a random instant between the two optional boundaries, falling back to the default range for
+/// any boundary that is absent
+fn date_time_between<R: Rng + ?Sized>(
+	rng: &mut R,
+	after: Option<DateTime<FixedOffset>>,
+	before: Option<DateTime<FixedOffset>>,
+) -> DateTime<Utc> {
+	let start = after.map_or_else(default_after, |a| a.with_timezone(&Utc));
+	let end = before.map_or_else(default_before, |b| b.with_timezone(&Utc));
+	Utc.timestamp(rng.gen_range(start.timestamp()..end.timestamp()), 0)
+}
+
+fn date_between<R: Rng + ?Sized>(
+	rng: &mut R,
+	after: Option<NaiveDate>,
+	before: Option<NaiveDate>,
+) -> NaiveDate {
+	let start = after.unwrap_or_else(|| default_after().naive_utc().date());
+	let end = before.unwrap_or_else(|| default_before().naive_utc().date());
+	NaiveDate::from_num_days_from_ce(
+		rng.gen_range(start.num_days_from_ce()..end.num_days_from_ce()),
+	)
+}
+
+fn time_between<R: Rng + ?Sized>(
+	rng: &mut R,
+	after: Option<NaiveTime>,
+	before: Option<NaiveTime>,
+) -> NaiveTime {
+	let start = after.map_or(0, |a| a.num_seconds_from_midnight());
+	let end = before.map_or(86_400, |b| b.num_seconds_from_midnight());
+	NaiveTime::from_num_seconds_from_midnight(rng.gen_range(start..end), 0)
+}
+
+/// Render a generated value as plain text, for output formats that have no notion of types.
+/// Strings are written without quotes, `null` is written as the literal `null` and any other
+/// value is written using its JSON representation
+///
+/// # Examples
+///
+/// ```rust
+/// use mockery::datatypes::plain_text;
+/// use serde_json::json;
+///
+/// assert_eq!(plain_text(&json!("Hello")), "Hello");
+/// assert_eq!(plain_text(&json!(23)), "23");
+/// assert_eq!(plain_text(&json!([51.2, -0.1])), "[51.2,-0.1]");
+/// ```
+pub fn plain_text(value: &Value) -> String {
+	match value {
+		Value::String(content) => content.clone(),
+		Value::Null => String::from("null"),
+		other => other.to_string(),
+	}
+}
+
+/// Convert the textual output of a numeric faker into a JSON number, falling back to the original
+/// text in the unlikely case that it can not be parsed
+fn coordinate(raw: String) -> Value {
+	match raw.parse::<f64>() {
+		Ok(number) => Value::from(number),
+		Err(_) => Value::String(raw),
+	}
+}
+
+/// Convert a string of digits into a JSON number where it will fit into a 64 bit integer. Longer
+/// numbers are kept as strings so that no precision is lost
+fn digits_to_value(digits: String) -> Value {
+	match digits.parse::<u64>() {
+		Ok(number) => Value::from(number),
+		Err(_) => Value::String(digits),
+	}
+}
+
+/// Create a random V4 UUID from the given source of randomness, rather than the operating system
+/// source used by `Uuid::new_v4`
+fn uuid_v4<R: Rng + ?Sized>(rng: &mut R) -> uuid::Uuid {
+	uuid::Builder::from_bytes(rng.gen())
+		.set_variant(uuid::Variant::RFC4122)
+		.set_version(uuid::Version::Random)
+		.build()
+}
+
 /// Create a number of a certain length. The value is returned as a string for display,
 /// and can therefore represent a number of arbitrary length at the expense of higher memory
 /// consumption.
@@ -161,16 +1141,15 @@ impl std::string::ToString for RandomData {
 /// # Examples
 ///
 /// ```rust
-/// let three_digit_number = number_with_length(3);
+/// let three_digit_number = number_with_length(&mut rand::thread_rng(), 3);
 /// println!("{}", three_digit_number);
 /// ```
-fn number_with_length(length: usize) -> String {
-	let mut random = rand::thread_rng();
+fn number_with_length<R: Rng + ?Sized>(random: &mut R, length: usize) -> String {
 	let mut buffer = String::with_capacity(length);
-	buffer = buffer + &format!("{}", random.gen_range(1, 10));
+	buffer = buffer + &format!("{}", random.gen_range(1..10));
 
 	for _ in 0..length - 1 {
-		buffer = buffer + &format!("{}", random.gen_range(0, 10));
+		buffer = buffer + &format!("{}", random.gen_range(0..10));
 	}
 
 	buffer
@@ -178,96 +1157,554 @@ fn number_with_length(length: usize) -> String {
 
 #[test]
 fn generate_number_format_of_correct_length() {
-	assert_eq!(number_with_length(1).len(), 1);
-	assert_eq!(number_with_length(2).len(), 2);
-	assert_eq!(number_with_length(10).len(), 10);
-	assert_eq!(number_with_length(1000).len(), 1000);
+	let mut rng = rand::thread_rng();
+	assert_eq!(number_with_length(&mut rng, 1).len(), 1);
+	assert_eq!(number_with_length(&mut rng, 2).len(), 2);
+	assert_eq!(number_with_length(&mut rng, 10).len(), 10);
+	assert_eq!(number_with_length(&mut rng, 1000).len(), 1000);
 }
 
-/// Use a `RandomData` definition to generate a random string of data
+/// Use a `RandomData` definition to generate a random piece of data. Textual data is returned
+/// as a JSON string, while numbers and coordinates are returned as their respective JSON types
 ///
 /// # Examples
 ///
 /// ```rust
-/// use mockery::datatypes::{RandomData, generate_fake_data};
+/// use mockery::datatypes::{RandomData, generate_fake_data, plain_text};
 /// println!(
 ///     "Hello {}, your new email address is {}",
-///     generate_fake_data(RandomData::FullName),
-///     generate_fake_data(RandomData::Email)
+///     plain_text(&generate_fake_data(RandomData::FullName)),
+///     plain_text(&generate_fake_data(RandomData::Email))
 /// )
 /// ```
-pub fn generate_fake_data(spec: RandomData) -> String {
+pub fn generate_fake_data(spec: RandomData) -> Value {
+	generate_fake_data_with_rng(spec, &mut rand::thread_rng())
+}
+
+/// Use a `RandomData` definition to generate a random piece of data, drawing all of its randomness
+/// from the given source
+///
+/// # Examples
+///
+/// ```rust
+/// use mockery::datatypes::{RandomData, generate_fake_data_with_rng};
+/// use rand::{rngs::StdRng, SeedableRng};
+///
+/// let first = generate_fake_data_with_rng(RandomData::UUID4, &mut StdRng::seed_from_u64(42));
+/// let second = generate_fake_data_with_rng(RandomData::UUID4, &mut StdRng::seed_from_u64(42));
+/// assert_eq!(first, second);
+/// ```
+pub fn generate_fake_data_with_rng<R: Rng + ?Sized>(spec: RandomData, rng: &mut R) -> Value {
+	generate_localized_data_with_rng(spec, Locale::EN, rng)
+}
+
+/// Generate a string with the `fake` faker in the given module, using the data of the given locale
+macro_rules! localized {
+	($locale:expr, $rng:expr, $module:ident :: $faker:ident) => {
+		match $locale {
+			Locale::EN => faker::$module::en::$faker().fake_with_rng::<String, _>($rng),
+			Locale::FR_FR => faker::$module::fr_fr::$faker().fake_with_rng::<String, _>($rng),
+			Locale::ZH_TW => faker::$module::zh_tw::$faker().fake_with_rng::<String, _>($rng),
+			Locale::ZH_CN => faker::$module::zh_cn::$faker().fake_with_rng::<String, _>($rng),
+			Locale::AR_SA => faker::$module::ar_sa::$faker().fake_with_rng::<String, _>($rng),
+			Locale::JA_JP => faker::$module::ja_jp::$faker().fake_with_rng::<String, _>($rng),
+			Locale::PT_BR => faker::$module::pt_br::$faker().fake_with_rng::<String, _>($rng),
+		}
+	};
+}
+
+/// Use a `RandomData` definition to generate a random piece of data for the given locale, drawing
+/// all of its randomness from the given source. A spec that asks for a type in a locale without
+/// data for it is an error, but that is only reported by `Specification::validate` and
+/// `RandomData::check_locale`. Callers that skip both get the type generated in English instead
+///
+/// # Examples
+///
+/// ```rust
+/// use mockery::datatypes::{generate_localized_data_with_rng, Locale, RandomData};
+///
+/// let name = generate_localized_data_with_rng(RandomData::FullName, Locale::ZH_TW, &mut rand::thread_rng());
+/// assert!(name.is_string());
+/// ```
+pub fn generate_localized_data_with_rng<R: Rng + ?Sized>(
+	spec: RandomData,
+	locale: Locale,
+	rng: &mut R,
+) -> Value {
+	let locale = match spec.check_locale(locale) {
+		Ok(()) => locale,
+		Err(_) => Locale::EN,
+	};
 	match spec {
-		RandomData::FirstName => format!("{}", faker::name::en::FirstName().fake::<String>()),
-		RandomData::LastName => format!("{}", faker::name::en::LastName().fake::<String>()),
-		RandomData::FullName => format!("{}", faker::name::en::Name().fake::<String>()),
-		RandomData::Email => format!("{}", faker::internet::en::SafeEmail().fake::<String>()),
-		RandomData::Number { digits } => format!("{}", number_with_length(digits)),
-		RandomData::NumberBetween { min, max } => {
-			format!("{}", rand::thread_rng().gen_range(min, max))
-		}
-		RandomData::Paragraph => faker::lorem::en::Paragraph(1..2).fake::<String>(),
+		RandomData::FirstName => Value::from(localized!(locale, rng, name::FirstName)),
+		RandomData::LastName => Value::from(localized!(locale, rng, name::LastName)),
+		RandomData::FullName => Value::from(localized!(locale, rng, name::Name)),
+		RandomData::Email => Value::from(localized!(locale, rng, internet::SafeEmail)),
+		RandomData::Number { digits } => digits_to_value(number_with_length(rng, digits)),
+		RandomData::NumberBetween {
+			min,
+			max,
+			distribution: None,
+		} => Value::from(rng.gen_range(min..max)),
+		RandomData::NumberBetween {
+			min,
+			max,
+			distribution: Some(distribution),
+		} => {
+			let value = distribution.sample_between(rng, min as f64, max as f64);
+			Value::from((value.floor() as i64).max(min).min(max - 1))
+		}
+		RandomData::Float {
+			min,
+			max,
+			precision,
+			distribution,
+		} => {
+			let value = distribution
+				.unwrap_or_default()
+				.sample_between(rng, min, max);
+			match precision {
+				Some(precision) => {
+					let scale = 10f64.powi(precision as i32);
+					let (lower, upper) = scaled_bounds(min, max, precision);
+					// Rounding to the nearest value must not leave the bounds
+					let scaled = (value * scale).round().max(lower).min(upper - 1.0);
+					Value::from(scaled / scale)
+				}
+				None => Value::from(value),
+			}
+		}
+		RandomData::Decimal {
+			min,
+			max,
+			precision,
+			distribution,
+		} => Value::from(decimal_between(
+			rng,
+			min,
+			max,
+			precision.unwrap_or(DEFAULT_DECIMAL_PRECISION),
+			&distribution.unwrap_or_default(),
+		)),
+		RandomData::Boolean { probability } => {
+			Value::from(rng.gen_bool(probability.unwrap_or(0.5).max(0.0).min(1.0)))
+		}
+		RandomData::Paragraph => {
+			Value::from(faker::lorem::en::Paragraph(1..2).fake_with_rng::<String, _>(rng))
+		}
 		RandomData::Paragraphs { amount } => {
 			let val = amount.unwrap_or(1usize);
-			format!(
-				"{}",
-				faker::lorem::en::Paragraph(val..val + 1).fake::<String>()
-			)
+			Value::from(faker::lorem::en::Paragraph(val..val + 1).fake_with_rng::<String, _>(rng))
+		}
+		RandomData::Sentence => {
+			Value::from(faker::lorem::en::Sentence(1..2).fake_with_rng::<String, _>(rng))
 		}
-		RandomData::Sentence => faker::lorem::en::Sentence(1..2).fake::<String>(),
 		RandomData::Sentences { amount } => {
 			let val = amount.unwrap_or(1usize);
-			format!(
-				"{}",
-				faker::lorem::en::Sentence(val..val + 1).fake::<String>()
-			)
+			Value::from(faker::lorem::en::Sentence(val..val + 1).fake_with_rng::<String, _>(rng))
 		}
-		RandomData::Company => format!("{}", faker::company::en::CompanyName().fake::<String>()),
-		RandomData::City => format!("{}", faker::address::en::CityName().fake::<String>()),
-		RandomData::StreetAddress => {
-			format!("{}", faker::address::en::StreetName().fake::<String>())
-		}
-		RandomData::Latitude => format!("{}", faker::address::en::Latitude().fake::<String>()),
-		RandomData::Longitude => format!("{}", faker::address::en::Longitude().fake::<String>()),
-		RandomData::LatLong => format!(
-			r#"[{}, {}]"#,
-			faker::address::en::Latitude().fake::<String>(),
-			faker::address::en::Longitude().fake::<String>()
-		),
-		RandomData::LongLat => format!(
-			r#"[{}, {}]"#,
-			faker::address::en::Longitude().fake::<String>(),
-			faker::address::en::Latitude().fake::<String>()
-		),
-		RandomData::GeoPoint => format!(
+		RandomData::Company => Value::from(localized!(locale, rng, company::CompanyName)),
+		RandomData::City => {
+			Value::from(faker::address::en::CityName().fake_with_rng::<String, _>(rng))
+		}
+		RandomData::StreetAddress => Value::from(localized!(locale, rng, address::StreetName)),
+		RandomData::Latitude => {
+			coordinate(faker::address::en::Latitude().fake_with_rng::<String, _>(rng))
+		}
+		RandomData::Longitude => {
+			coordinate(faker::address::en::Longitude().fake_with_rng::<String, _>(rng))
+		}
+		RandomData::LatLong => Value::Array(vec![
+			coordinate(faker::address::en::Latitude().fake_with_rng::<String, _>(rng)),
+			coordinate(faker::address::en::Longitude().fake_with_rng::<String, _>(rng)),
+		]),
+		RandomData::LongLat => Value::Array(vec![
+			coordinate(faker::address::en::Longitude().fake_with_rng::<String, _>(rng)),
+			coordinate(faker::address::en::Latitude().fake_with_rng::<String, _>(rng)),
+		]),
+		RandomData::GeoPoint => Value::from(format!(
 			r#"POINT({} {})"#,
-			faker::address::en::Longitude().fake::<String>(),
-			faker::address::en::Latitude().fake::<String>()
-		),
-		RandomData::Postcode => format!("{}", faker::address::en::PostCode().fake::<String>()),
-		RandomData::FullAddress => format!(
+			faker::address::en::Longitude().fake_with_rng::<String, _>(rng),
+			faker::address::en::Latitude().fake_with_rng::<String, _>(rng)
+		)),
+		RandomData::Postcode => Value::from(localized!(locale, rng, address::PostCode)),
+		RandomData::FullAddress => Value::from(format!(
 			"{}, {}, {}",
-			faker::address::en::StreetName().fake::<String>(),
-			faker::address::en::CityName().fake::<String>(),
-			faker::address::en::PostCode().fake::<String>()
-		),
-		RandomData::UUID4 => format!("{}", uuid::Uuid::new_v4()),
-		RandomData::PhoneNumber => format!(
-			"{}",
-			faker::phone_number::en::PhoneNumber().fake::<String>()
-		),
+			faker::address::en::StreetName().fake_with_rng::<String, _>(rng),
+			faker::address::en::CityName().fake_with_rng::<String, _>(rng),
+			faker::address::en::PostCode().fake_with_rng::<String, _>(rng)
+		)),
+		RandomData::UUID4 => Value::from(format!("{}", uuid_v4(rng))),
+		RandomData::PhoneNumber => Value::from(localized!(locale, rng, phone_number::PhoneNumber)),
 		RandomData::LoremPicsum {
 			width,
 			height,
 			grayscale,
-		} => format!(
+		} => Value::from(format!(
 			"https://picusm.photos/{}{}/{}",
 			if grayscale.unwrap_or(false) { "g/" } else { "" },
 			width.unwrap_or(200),
 			height.unwrap_or(200)
+		)),
+		RandomData::Date {
+			after,
+			before,
+			format,
+		} => Value::from(
+			date_between(rng, after, before)
+				.format(format.as_ref().map_or("%Y-%m-%d", String::as_str))
+				.to_string(),
 		),
-		RandomData::NullValue => format!("null"),
-		RandomData::String { content } => content.clone(),
-		RandomData::Reference { .. } => format!("null"),
+		RandomData::Time {
+			after,
+			before,
+			format,
+		} => Value::from(
+			time_between(rng, after, before)
+				.format(format.as_ref().map_or("%H:%M:%S", String::as_str))
+				.to_string(),
+		),
+		RandomData::DateTime {
+			after,
+			before,
+			format,
+			timezone,
+		} => {
+			let offset = timezone.map_or(FixedOffset::east(0), |tz| tz.0);
+			let value = date_time_between(rng, after, before).with_timezone(&offset);
+			match format {
+				Some(format) => Value::from(value.format(&format).to_string()),
+				None => Value::from(value.to_rfc3339()),
+			}
+		}
+		RandomData::UnixTimestamp { after, before } => {
+			Value::from(date_time_between(rng, after, before).timestamp())
+		}
+		RandomData::OneOf { values, weights } => {
+			let candidates: Vec<usize> = (0..values.len()).collect();
+			pick_position(rng, &candidates, &weights)
+				.map_or(Value::Null, |position| values[position].clone())
+		}
+		RandomData::ManyOf {
+			values,
+			weights,
+			min,
+			max,
+		} => {
+			let min = min.unwrap_or(0).min(values.len());
+			let max = max
+				.unwrap_or_else(|| values.len())
+				.min(values.len())
+				.max(min);
+			let amount = rng.gen_range(min..=max);
+
+			let mut candidates: Vec<usize> = (0..values.len()).collect();
+			let mut picked = Vec::with_capacity(amount);
+			for _ in 0..amount {
+				match pick_position(rng, &candidates, &weights) {
+					Some(position) => picked.push(candidates.remove(position)),
+					None => break,
+				}
+			}
+			picked.sort();
+			Value::Array(
+				picked
+					.into_iter()
+					.map(|index| values[index].clone())
+					.collect(),
+			)
+		}
+		RandomData::Pattern { regex, max_repeat } => PATTERNS.with(|patterns| {
+			let mut patterns = patterns.borrow_mut();
+			if !patterns.contains_key(&regex) {
+				match parse_pattern(&regex) {
+					Ok(hir) => patterns.insert(regex.clone(), hir),
+					Err(_) => return Value::Null,
+				};
+			}
+			let mut output = String::new();
+			write_pattern(
+				rng,
+				&patterns[&regex],
+				max_repeat.unwrap_or(DEFAULT_MAX_REPEAT),
+				&mut output,
+			);
+			Value::from(output)
+		}),
+		RandomData::NullValue => Value::Null,
+		RandomData::String { content } => Value::from(content),
+		RandomData::Reference { .. } => Value::Null,
+	}
+}
+
+#[test]
+fn generate_typed_values() {
+	assert!(generate_fake_data(RandomData::NumberBetween {
+		min: 18,
+		max: 25,
+		distribution: None
+	})
+	.is_u64());
+	assert!(generate_fake_data(RandomData::NumberBetween {
+		min: -25,
+		max: -18,
+		distribution: None
+	})
+	.is_i64());
+	assert!(generate_fake_data(RandomData::Boolean { probability: None }).is_boolean());
+	assert_eq!(
+		generate_fake_data(RandomData::Boolean {
+			probability: Some(1.0)
+		}),
+		true
+	);
+	assert!(generate_fake_data(RandomData::Float {
+		min: -1.5,
+		max: 1.5,
+		precision: Some(1),
+		distribution: None
+	})
+	.is_f64());
+	assert!(generate_fake_data(RandomData::Number { digits: 4 }).is_u64());
+	assert!(generate_fake_data(RandomData::Number { digits: 40 }).is_string());
+	assert!(generate_fake_data(RandomData::Latitude).is_f64());
+	assert!(generate_fake_data(RandomData::NullValue).is_null());
+	assert_eq!(
+		generate_fake_data(RandomData::LatLong)
+			.as_array()
+			.map(|pair| pair.len()),
+		Some(2)
+	);
+}
+
+#[test]
+fn generate_dates_within_bounds() {
+	let after = NaiveDate::from_ymd(2019, 1, 1);
+	let before = NaiveDate::from_ymd(2019, 1, 3);
+	for _ in 0..20 {
+		let date = generate_fake_data(RandomData::Date {
+			after: Some(after),
+			before: Some(before),
+			format: None,
+		});
+		assert!(date == "2019-01-01" || date == "2019-01-02");
 	}
+
+	let timezone = Timezone::try_from(String::from("+05:30")).unwrap();
+	let date_time = generate_fake_data(RandomData::DateTime {
+		after: None,
+		before: None,
+		format: None,
+		timezone: Some(timezone),
+	});
+	assert!(date_time.as_str().unwrap().ends_with("+05:30"));
+
+	let midnight = NaiveTime::from_hms(0, 0, 0);
+	assert!(RandomData::Time {
+		after: None,
+		before: Some(midnight),
+		format: None,
+	}
+	.validate()
+	.is_err());
+	for format in &["%Q", "%Y-%m-%", "%H:%M"] {
+		assert!(RandomData::Date {
+			after: None,
+			before: None,
+			format: Some(format.to_string()),
+		}
+		.validate()
+		.is_err());
+	}
+}
+
+#[test]
+fn generate_choices_from_the_given_values() {
+	let statuses = vec![
+		Value::from("active"),
+		Value::from("suspended"),
+		Value::from("deleted"),
+	];
+	for _ in 0..20 {
+		let status = generate_fake_data(RandomData::OneOf {
+			values: statuses.clone(),
+			weights: Some(vec![0.0, 1.0, 0.0]),
+		});
+		assert_eq!(status, "suspended");
+
+		let picked = generate_fake_data(RandomData::ManyOf {
+			values: statuses.clone(),
+			weights: None,
+			min: Some(1),
+			max: Some(2),
+		});
+		let picked = picked.as_array().unwrap();
+		assert!(!picked.is_empty() && picked.len() <= 2);
+		let positions: Vec<usize> = picked
+			.iter()
+			.map(|value| statuses.iter().position(|status| status == value).unwrap())
+			.collect();
+		assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+	}
+
+	assert!(RandomData::OneOf {
+		values: statuses.clone(),
+		weights: Some(vec![1.0]),
+	}
+	.validate()
+	.is_err());
+	assert!(RandomData::ManyOf {
+		values: statuses.clone(),
+		weights: Some(vec![1.0, 0.0, 1.0]),
+		min: Some(3),
+		max: None,
+	}
+	.validate()
+	.is_err());
+}
+
+#[test]
+fn generate_decimals_with_exact_precision() {
+	let mut rng = rand::thread_rng();
+	for _ in 0..50 {
+		let price = decimal_between(&mut rng, 0.0, 1.0, 2, &NumberDistribution::Uniform);
+		assert!(price.starts_with("0.") && price.len() == 4, "{}", price);
+		let balance = decimal_between(&mut rng, -0.5, 0.0, 3, &NumberDistribution::Uniform);
+		assert!(
+			balance.starts_with("-0.") && balance.len() == 6,
+			"{}",
+			balance
+		);
+		let share = generate_fake_data(RandomData::Float {
+			min: 0.04,
+			max: 0.2,
+			precision: Some(1),
+			distribution: None,
+		});
+		assert_eq!(share, 0.1);
+	}
+	assert_eq!(
+		decimal_between(&mut rng, 5.0, 5.0, 0, &NumberDistribution::Uniform),
+		"5"
+	);
+	assert!(RandomData::Decimal {
+		min: 0.05,
+		max: 0.06,
+		precision: Some(1),
+		distribution: None,
+	}
+	.validate()
+	.is_err());
+}
+
+#[test]
+fn distributions_stay_within_bounds() {
+	use rand::{rngs::StdRng, SeedableRng};
+
+	let mut rng = StdRng::seed_from_u64(7);
+	let distributions = vec![
+		NumberDistribution::Normal {
+			mean: 35.0,
+			std_dev: 12.0,
+		},
+		NumberDistribution::LogNormal {
+			mean: 3.0,
+			std_dev: 1.0,
+		},
+		NumberDistribution::Exponential { rate: 0.1 },
+		NumberDistribution::Poisson { mean: 4.0 },
+		NumberDistribution::Zipf { exponent: 1.2 },
+	];
+	for distribution in distributions {
+		for _ in 0..200 {
+			let value = distribution.sample_between(&mut rng, 18.0, 100.0);
+			assert!(
+				value >= 18.0 && value < 100.0,
+				"{:?} gave {}",
+				distribution,
+				value
+			);
+		}
+	}
+
+	let far = NumberDistribution::Normal {
+		mean: 1000.0,
+		std_dev: 1.0,
+	};
+	assert!(far.sample_between(&mut rng, 18.0, 100.0) < 100.0);
+	assert!(far.sample_between(&mut rng, -1.0, 0.0) < 0.0);
+
+	let ranks: Vec<f64> = (0..1000)
+		.map(|_| NumberDistribution::Zipf { exponent: 2.0 }.sample_between(&mut rng, 1.0, 1000.0))
+		.collect();
+	let firsts = ranks.iter().filter(|rank| **rank == 1.0).count();
+	assert!(firsts > 500, "rank 1 was only drawn {} times", firsts);
+
+	assert!(NumberDistribution::Normal {
+		mean: 0.0,
+		std_dev: 0.0
+	}
+	.validate()
+	.is_err());
+}
+
+#[test]
+fn generate_strings_matching_a_pattern() {
+	let patterns = [
+		"ACC-[0-9]{8}",
+		r"^[A-CEGHJ-PR-TW-Z]{2}\d{6}[A-D]$",
+		r"(SKU|PRD)_[a-z]+\.\w*",
+		"[^a-z]?x{2,}",
+	];
+	for pattern in patterns.iter() {
+		let matcher = regex::Regex::new(&format!("^(?:{})$", pattern)).unwrap();
+		for _ in 0..100 {
+			let value = generate_fake_data(RandomData::Pattern {
+				regex: pattern.to_string(),
+				max_repeat: Some(3),
+			});
+			let text = value.as_str().unwrap();
+			assert!(
+				matcher.is_match(text),
+				"{} does not match {}",
+				text,
+				pattern
+			);
+			assert!(text.chars().all(|c| c.is_ascii_graphic() || c == ' '));
+		}
+	}
+
+	for pattern in ["a\\bb", "a^b", "(unclosed", "(?P<x>a)\\1"].iter() {
+		assert!(RandomData::Pattern {
+			regex: pattern.to_string(),
+			max_repeat: None,
+		}
+		.validate()
+		.is_err());
+	}
+}
+
+#[test]
+fn locales_cover_the_types_they_have_data_for() {
+	assert!(RandomData::FullName.check_locale(Locale::ZH_TW).is_ok());
+	assert!(RandomData::UUID4.check_locale(Locale::ZH_TW).is_ok());
+	assert!(RandomData::City.check_locale(Locale::EN).is_ok());
+	assert_eq!(
+		RandomData::City.check_locale(Locale::ZH_TW),
+		Err(String::from("City is not available in the `zh_TW` locale"))
+	);
+
+	let name = generate_localized_data_with_rng(
+		RandomData::LastName,
+		Locale::ZH_TW,
+		&mut rand::thread_rng(),
+	);
+	assert!(name.as_str().unwrap().chars().all(|c| !c.is_ascii()));
+	assert!(RandomData::PhoneNumber.check_locale(Locale::FR_FR).is_ok());
+	assert!(RandomData::Postcode.check_locale(Locale::PT_BR).is_ok());
+	assert_eq!(
+		serde_json::from_str::<Locale>(r#""ja_JP""#).unwrap(),
+		Locale::JA_JP
+	);
+	assert!(serde_json::from_str::<Locale>(r#""de_DE""#).is_err());
 }
@@ -36,6 +36,9 @@ impl GenerationSpecification {
 pub enum OutputType {
 	JSON,
 	CSV,
+	SQL,
+	/// Newline delimited JSON, with one model instance per line
+	NDJSON,
 }
 
 impl OutputType {
@@ -44,6 +47,8 @@ impl OutputType {
 		match self {
 			JSON => "json",
 			CSV => "csv",
+			SQL => "sql",
+			NDJSON => "jsonl",
 		}
 	}
 }
@@ -142,6 +147,12 @@ pub mod io {
 								writer.write_record(&row)?;
 							}
 						}
+						OutputType::SQL | OutputType::NDJSON => {
+							return Err(Error::new(
+								ErrorKind::InvalidInput,
+								"This output type is only supported by the `generator` module",
+							));
+						}
 					}
 
 					let model_refs = model.get_reference_types();
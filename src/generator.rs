@@ -1,9 +1,12 @@
-use crate::datatypes::RandomData;
+use crate::datatypes::{plain_text, Locale, RandomData};
 use crate::generation::OutputType;
-use crate::specification::{DataType as DT, Model, Specification};
+use crate::specification::{
+	DataType as DT, FieldSource, Model, RunPlan, Selection, SequenceScope, Specification,
+	TemplatePart, UniqueKey, UniqueScope, DEFAULT_SEQUENCE_START, NAMESPACE_SEPARATOR,
+};
 
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::str::FromStr;
@@ -13,56 +16,35 @@ use std::convert::AsRef;
 use std::fs::{create_dir_all, read_to_string, File};
 use std::iter::FromIterator;
 
-use csv::Writer as Csv;
-use serde_json::{from_str, to_string};
-use std::io::Write;
+use csv::{QuoteStyle as CsvQuoteStyle, Terminator, WriterBuilder};
+use serde_json::{from_str, to_string, Value};
+use std::io::{BufWriter, Write};
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_derive::{Deserialize, Serialize};
 
 // ---------------------------------
 
-pub type ModelDataMap = HashMap<String, Vec<HashMap<String, String>>>;
+pub type ModelData = BTreeMap<String, Value>;
+pub type ModelDataMap = HashMap<String, Vec<ModelData>>;
+/// The values of each unique key that are already used, serialised to JSON and grouped by the model
+/// and the properties of the key
+type UsedKeys = HashMap<(String, Vec<String>), HashSet<String>>;
 
 fn get_model_children(model: &Model) -> Vec<String> {
 	model
 		.type_iter()
-		.filter_map(|(key, data_type)| {
-			if let DT::Model(def) = data_type {
-				Some(def.clone())
-			} else if let DT::List(nested) = data_type {
-				match nested.borrow() {
-					DT::Model(def) => Some(def.clone()),
-					_ => None,
-				}
-			} else {
-				None
-			}
-		})
+		.filter_map(|(_, data_type)| child_model_name(data_type).cloned())
 		.collect()
 }
 
-type BoolResult = Result<(), String>;
-fn validate_dependencies(deps: &Vec<String>, spec: &Specification) -> BoolResult {
-	let types_valid: Vec<BoolResult> = deps
-		.iter()
-		.map(|data_type| {
-			if spec.has_model(data_type) {
-				Ok(())
-			} else {
-				Err(format!("No such type {}", data_type))
-			}
-		})
-		.collect();
-
-	for e in types_valid {
-		e?;
-	}
-
-	Ok(())
-}
-
 #[derive(Clone, Debug)]
 struct GenData {
+	model_type: String,
 	model: Model,
-	data: HashMap<String, String>,
+	data: ModelData,
 }
 
 #[derive(Clone, Debug)]
@@ -70,6 +52,12 @@ struct GenContext {
 	pub parent_context: Option<Box<GenContext>>,
 	pub parent_model: Option<GenData>,
 	pub models: ModelDataMap,
+	/// The position of the instance being generated amongst those generated by the same property
+	/// of its parent, or amongst the roots of its model
+	pub index: usize,
+	/// The values of the parent scoped unique keys used by the instances generated before this one
+	/// by the same property of its parent
+	pub sibling_keys: UsedKeys,
 }
 
 enum RefType {
@@ -88,7 +76,7 @@ impl FromStr for RefType {
 }
 
 impl GenContext {
-	pub fn add_model_data(&mut self, data_type: String, data_values: HashMap<String, String>) {
+	pub fn add_model_data(&mut self, data_type: String, data_values: ModelData) {
 		let list = self.models.entry(data_type).or_insert(Vec::new());
 		list.push(data_values);
 	}
@@ -98,7 +86,7 @@ impl GenContext {
 			list.append(values);
 		});
 	}
-	pub fn fetch_ref_path(&self, parts: Vec<String>) -> Option<HashMap<String, String>> {
+	pub fn fetch_ref_path(&self, parts: Vec<String>) -> Option<ModelData> {
 		if parts.len() == 0 {
 			None
 		} else if parts.len() == 1 {
@@ -130,107 +118,848 @@ impl GenContext {
 			}
 		}
 	}
+	/// Count the instances of the given model that contain the model being generated in this
+	/// context, following the chain of parents up to the root
+	pub fn ancestor_count(&self, model_type: &str) -> usize {
+		let own = match &self.parent_model {
+			Some(parent) if parent.model_type == model_type => 1,
+			_ => 0,
+		};
+		own + self
+			.parent_context
+			.as_ref()
+			.map_or(0, |parent_ctx| parent_ctx.ancestor_count(model_type))
+	}
+	/// Collect every instance of the given model that has been generated so far, across this
+	/// context and all of its ancestors. Instances are returned in the order they were generated
+	pub fn model_rows<'a>(
+		&'a self,
+		model_type: &'a str,
+	) -> Box<dyn Iterator<Item = &'a ModelData> + 'a> {
+		let inherited: Box<dyn Iterator<Item = &'a ModelData> + 'a> = match &self.parent_context {
+			Some(parent_ctx) => parent_ctx.model_rows(model_type),
+			None => Box::new(std::iter::empty()),
+		};
+		Box::new(inherited.chain(self.models.get(model_type).into_iter().flatten()))
+	}
+	/// Count every instance of the given model that has been generated so far, across this context
+	/// and all of its ancestors, without collecting them
+	pub fn count_model_rows(&self, model_type: &str) -> usize {
+		self.models.get(model_type).map_or(0, Vec::len)
+			+ self
+				.parent_context
+				.as_ref()
+				.map_or(0, |parent_ctx| parent_ctx.count_model_rows(model_type))
+	}
+	/// Find the instance of the given model at `position` in the order that `model_rows`
+	/// returns them, without collecting them
+	pub fn model_row(&self, model_type: &str, position: usize) -> Option<&ModelData> {
+		let inherited = match &self.parent_context {
+			Some(parent_ctx) => {
+				let inherited = parent_ctx.count_model_rows(model_type);
+				if position < inherited {
+					return parent_ctx.model_row(model_type, position);
+				}
+				inherited
+			}
+			None => 0,
+		};
+		self.models.get(model_type)?.get(position - inherited)
+	}
+}
+
+/// Choose one of the existing instances of `model` to point a `ForeignKey` at. Round robin
+/// selection follows the position of the instance being generated amongst those of `model_type`
+fn select_row<'a>(
+	ctx: &'a GenContext,
+	model: &str,
+	model_type: &str,
+	selection: &Selection,
+	rng: &mut StdRng,
+) -> Option<&'a ModelData> {
+	let count = ctx.count_model_rows(model);
+	if count == 0 {
+		return None;
+	}
+
+	let position = match selection {
+		Selection::Random => rng.gen_range(0..count),
+		Selection::RoundRobin => ctx.count_model_rows(model_type) % count,
+		Selection::Weighted { property } => {
+			let weights = ctx.model_rows(model).map(|row| {
+				row.get(property)
+					.and_then(Value::as_f64)
+					.filter(|weight| *weight > 0.0)
+					.unwrap_or(0.0)
+			});
+			match WeightedIndex::new(weights) {
+				Ok(distribution) => distribution.sample(rng),
+				Err(_) => rng.gen_range(0..count),
+			}
+		}
+	};
+
+	ctx.model_row(model, position)
 }
 
 pub fn from_spec(
 	model_name: String,
 	spec: Specification,
 	quantity: usize,
+	seed: Option<u64>,
 ) -> Result<ModelDataMap, String> {
-	let initial_model = spec.get_definition(&model_name);
-	let deps = get_model_children(initial_model);
+	let mut plan = RunPlan::new();
+	plan.add_model(model_name, quantity);
+	from_plan(&plan, spec, seed)
+}
 
-	validate_dependencies(&deps, &spec)?;
+/// Generate every root model in the plan into a single set of model data. Roots are generated one
+/// after the other, so a `ForeignKey` in one root can point at the instances of another. Roots that
+/// are pointed at are generated first
+pub fn from_plan(
+	plan: &RunPlan,
+	mut spec: Specification,
+	seed: Option<u64>,
+) -> Result<ModelDataMap, String> {
+	for (model_name, _) in plan.model_iter() {
+		if !spec.has_model(model_name) {
+			return Err(format!("No such model {}", model_name));
+		}
+	}
+	// Specs that were not read by `io::read_spec` may still have models that extend others
+	spec.resolve_extends()?;
+	if let Err(errors) = spec.validate() {
+		let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+		return Err(messages.join("\n"));
+	}
 
 	let mut initial_context = GenContext {
 		parent_context: None,
 		parent_model: None,
 		models: HashMap::new(),
+		index: 0,
+		sibling_keys: UsedKeys::new(),
 	};
+	let mut used_keys = UsedKeys::new();
 
-	for _ in 0..quantity {
-		generate_model_data(
-			model_name.clone(),
-			initial_model,
-			&mut initial_context,
-			&spec,
-		);
+	let mut rng = match seed {
+		Some(seed) => StdRng::seed_from_u64(seed),
+		None => StdRng::from_entropy(),
+	};
+
+	let roots = plan
+		.model_iter()
+		.map(|(model_name, quantity)| (Some(model_name.clone()), (model_name, *quantity)))
+		.collect();
+
+	for (model_name, quantity) in order_by_foreign_keys(roots, &spec) {
+		let initial_model = match spec.get_definition(model_name) {
+			Some(model) => model,
+			None => return Err(format!("No such model {}", model_name)),
+		};
+		for index in 0..quantity {
+			initial_context.index = index;
+			generate_model_data(
+				model_name.clone(),
+				initial_model,
+				&mut initial_context,
+				&spec,
+				&mut used_keys,
+				&mut rng,
+			)?;
+		}
 	}
 
 	Ok(initial_context.models)
 }
 
-fn generate_model_data(
-	model_type: String,
-	model: &Model,
-	ctx: &mut GenContext,
-	spec: &Specification,
-) {
-	let mut model_data: HashMap<String, String> = HashMap::new();
-	let mut child_models: Vec<(String, DT)> = Vec::new();
+/// Decide whether a property should be `null` for the instance being generated
+fn roll_null(data_type: &DT, rng: &mut StdRng) -> bool {
+	match data_type {
+		DT::Nullable { probability, .. } => rng.gen_bool(probability.max(0.0).min(1.0)),
+		_ => false,
+	}
+}
 
-	model
-		.type_iter()
-		.for_each(|(property, data_type)| match data_type {
-			DT::RandomData(random_data) => {
-				let data = random_data.to_string();
-				&model_data.insert(property.clone(), data);
+/// Generate the value of a property that is stored directly on a model, rather than as a separate
+/// model. Lists of values become arrays, while references that can not be resolved produce nothing.
+/// `row` holds the properties of the model that have been generated so far, for templates to use
+fn generate_value(
+	data_type: &DT,
+	model_type: &str,
+	model: &Model,
+	row: &ModelData,
+	locale: Locale,
+	ctx: &GenContext,
+	rng: &mut StdRng,
+) -> Result<Option<Value>, String> {
+	Ok(match data_type {
+		DT::RandomData(random_data) => Some(
+			random_data
+				.clone()
+				.into_localized_data_with_rng(locale, rng),
+		),
+		DT::Localized { of, locale } => {
+			generate_value(of, model_type, model, row, *locale, ctx, rng)?
+		}
+		DT::List(nested) => {
+			let mut values = Vec::new();
+			for _ in 0..nested.length(rng) {
+				let item = nested.item_type();
+				if let Some(value) = generate_value(item, model_type, model, row, locale, ctx, rng)?
+				{
+					values.push(value);
+				}
 			}
-			DT::Model(_) => {
-				child_models.push((property.clone(), data_type.clone()));
+			Some(Value::Array(values))
+		}
+		DT::Reference { path, property } => {
+			let parts = path.split("~").map(String::from).collect();
+			ctx.fetch_ref_path(parts)
+				.and_then(|data_set| data_set.get(property).cloned())
+		}
+		DT::ForeignKey {
+			model,
+			property,
+			selection,
+		} => {
+			let selection = selection.clone().unwrap_or_default();
+			match select_row(ctx, model, model_type, &selection, rng) {
+				Some(row) => row.get(property).cloned(),
+				None => {
+					return Err(format!(
+						"ForeignKey points at `{}`, which has no generated instances",
+						model
+					))
+				}
 			}
-			DT::List(_) => {
-				child_models.push((property.clone(), data_type.clone()));
+		}
+		DT::Sequence { start, step, scope } => {
+			let position = match scope.unwrap_or_default() {
+				SequenceScope::Global => ctx.count_model_rows(model_type),
+				SequenceScope::Parent => ctx.index,
+			};
+			let offset = step.unwrap_or(1).saturating_mul(position as i64);
+			Some(Value::from(
+				start
+					.unwrap_or(DEFAULT_SEQUENCE_START)
+					.saturating_add(offset),
+			))
+		}
+		DT::Unique { of, .. } => generate_value(of, model_type, model, row, locale, ctx, rng)?,
+		DT::Nullable { of, .. } => {
+			if roll_null(data_type, rng) {
+				Some(Value::Null)
+			} else {
+				generate_value(of, model_type, model, row, locale, ctx, rng)?
 			}
-			DT::Reference {
-				ref path,
-				property: ref_prop,
-			} => {
-				let parts = path.split("~").map(String::from).collect();
-				let ref_model_data = ctx.fetch_ref_path(parts);
-				match ref_model_data {
-					Some(data_set) => {
-						let data = data_set.get(ref_prop).unwrap();
-						&model_data.insert(property.clone(), data.clone());
+		}
+		DT::Template(template) => {
+			// Each field is only generated once, so that a name used twice has the same value
+			let mut fields: HashMap<String, Option<Value>> = HashMap::new();
+			let mut text = String::new();
+			let parts = match template.parts() {
+				Ok(parts) => parts,
+				Err(_) => return Ok(None),
+			};
+			for part in parts {
+				let field = match part {
+					TemplatePart::Text(part) => {
+						text.push_str(&part);
+						continue;
 					}
-					None => {}
+					TemplatePart::Field(field) => field,
+				};
+				if !fields.contains_key(&field.name) {
+					let value = match template.source(&field.name, model) {
+						Ok(FieldSource::Sibling(property)) => row.get(property).cloned(),
+						Ok(FieldSource::Value(value)) => {
+							generate_value(value, model_type, model, row, locale, ctx, rng)?
+						}
+						Ok(FieldSource::Generated(value)) => {
+							generate_value(&value, model_type, model, row, locale, ctx, rng)?
+						}
+						Err(_) => None,
+					};
+					fields.insert(field.name.clone(), value);
 				}
+				let value = match &fields[&field.name] {
+					Some(Value::Null) | None => String::new(),
+					Some(value) => plain_text(value),
+				};
+				text.push_str(&field.pad(&value));
 			}
+			Some(Value::from(text))
+		}
+		DT::Model(_) => None,
+	})
+}
+
+/// The name of the model generated by a child model property, if it is one
+fn child_model_name(data_type: &DT) -> Option<&String> {
+	match data_type.element_type() {
+		DT::Model(relation) => Some(relation.name()),
+		_ => None,
+	}
+}
+
+/// Find every model that instances of the given model, or any of their children, point at through
+/// a `ForeignKey`
+fn foreign_key_targets(
+	model_type: &str,
+	spec: &Specification,
+	visited: &mut Vec<String>,
+) -> Vec<String> {
+	let model = match spec.get_definition(model_type) {
+		Some(model) if !visited.iter().any(|v| v == model_type) => model,
+		_ => return Vec::new(),
+	};
+	visited.push(model_type.to_string());
+
+	let mut targets = Vec::new();
+	for (_, data_type) in model.type_iter() {
+		match data_type.element_type() {
+			DT::ForeignKey { model, .. } => targets.push(model.clone()),
+			DT::Model(child) => targets.extend(foreign_key_targets(child.name(), spec, visited)),
 			_ => {}
-		});
+		}
+	}
+	targets
+}
 
-	ctx.add_model_data(model_type, model_data.clone());
+/// Order a set of items that each generate instances of a model, so that an item is generated
+/// before any other item that points at its instances through a `ForeignKey`. Items are otherwise
+/// kept in their original order
+fn order_by_foreign_keys<T>(items: Vec<(Option<String>, T)>, spec: &Specification) -> Vec<T> {
+	let mut remaining: Vec<(Option<String>, T, Vec<String>)> = items
+		.into_iter()
+		.map(|(model_name, item)| {
+			let targets = model_name.as_ref().map_or(Vec::new(), |name| {
+				foreign_key_targets(name, spec, &mut Vec::new())
+			});
+			(model_name, item, targets)
+		})
+		.collect();
 
-	child_models.iter().for_each(|(property, model_type)| {
-		let (gen_name, iterations) = if let DT::List(nested) = model_type {
-			match nested.borrow() {
-				DT::Model(next_model_name) => (next_model_name.clone(), 5),
-				_ => return,
-			}
-		} else if let DT::Model(next_model_name) = model_type {
-			(next_model_name.clone(), 1)
+	let mut ordered = Vec::with_capacity(remaining.len());
+	while !remaining.is_empty() {
+		let next = remaining
+			.iter()
+			.enumerate()
+			.position(|(index, (_, _, targets))| {
+				!remaining
+					.iter()
+					.enumerate()
+					.any(|(other_index, (other, _, _))| {
+						other_index != index
+							&& other.as_ref().map_or(false, |name| targets.contains(name))
+					})
+			})
+			.unwrap_or(0);
+		let (_, item, _) = remaining.remove(next);
+		ordered.push(item);
+	}
+	ordered
+}
+
+/// The number of times an instance is generated again when it repeats the values of a unique key,
+/// before giving up
+const MAX_UNIQUE_ATTEMPTS: usize = 100;
+
+/// Generate every property of a model that is stored directly on it. Templates are generated
+/// last, each after any other templates that it uses
+fn generate_row(
+	model_type: &str,
+	model: &Model,
+	locale: Locale,
+	ctx: &GenContext,
+	rng: &mut StdRng,
+) -> Result<ModelData, String> {
+	let generate = |property: &str, data_type: &DT, row: &ModelData, rng: &mut StdRng| {
+		generate_value(data_type, model_type, model, row, locale, ctx, rng)
+			.map_err(|reason| format!("`{}.{}`: {}", model_type, property, reason))
+	};
+	let mut model_data: ModelData = BTreeMap::new();
+	let mut templates: Vec<(&String, &DT)> = Vec::new();
+
+	for (property, data_type) in model.type_iter() {
+		if child_model_name(data_type).is_some() {
+			continue;
+		} else if let DT::Template(_) = data_type.element_type() {
+			templates.push((property, data_type));
+		} else if let Some(data) = generate(property, data_type, &model_data, rng)? {
+			model_data.insert(property.clone(), data);
+		}
+	}
+
+	while !templates.is_empty() {
+		let next = templates
+			.iter()
+			.position(|(_, data_type)| {
+				model
+					.template_dependencies(data_type)
+					.iter()
+					.all(|dependency| {
+						!templates
+							.iter()
+							.any(|(property, _)| *property == dependency)
+					})
+			})
+			.unwrap_or(0);
+		let (property, data_type) = templates.remove(next);
+		if let Some(data) = generate(property, data_type, &model_data, rng)? {
+			model_data.insert(property.clone(), data);
+		}
+	}
+
+	Ok(model_data)
+}
+
+/// Serialise the values of a unique key in `row`. Keys where any of the values is `null` or missing
+/// have no value, and are not checked
+fn key_value(key: &UniqueKey, row: &ModelData) -> Option<String> {
+	let values = key
+		.properties()
+		.iter()
+		.map(|property| row.get(property).filter(|value| !value.is_null()))
+		.collect::<Option<Vec<&Value>>>()?;
+	to_string(&values).ok()
+}
+
+/// Whether a unique key only has to be unique amongst the instances that share a parent with the
+/// one being generated. Root models have no parent, so their keys are unique across the model
+fn in_sibling_scope(key: &UniqueKey, ctx: &GenContext) -> bool {
+	key.scope() == UniqueScope::Parent && ctx.parent_model.is_some()
+}
+
+/// Find a unique key of the model whose values in `row` are already used by another instance in
+/// the key's scope
+fn repeated_key(
+	model_type: &str,
+	model: &Model,
+	row: &ModelData,
+	ctx: &GenContext,
+	used: &UsedKeys,
+) -> Option<UniqueKey> {
+	model.unique_keys().into_iter().find(|key| {
+		let value = match key_value(key, row) {
+			Some(value) => value,
+			None => return false,
+		};
+		let used = if in_sibling_scope(key, ctx) {
+			&ctx.sibling_keys
 		} else {
-			return;
+			used
 		};
+		used.get(&(model_type.to_string(), key.properties().clone()))
+			.map_or(false, |values| values.contains(&value))
+	})
+}
 
+/// Mark the values of each unique key in `row` as used within the key's scope
+fn use_keys(
+	model_type: &str,
+	model: &Model,
+	row: &ModelData,
+	ctx: &mut GenContext,
+	used: &mut UsedKeys,
+) {
+	for key in model.unique_keys() {
+		let value = match key_value(&key, row) {
+			Some(value) => value,
+			None => continue,
+		};
+		let used = if in_sibling_scope(&key, ctx) {
+			&mut ctx.sibling_keys
+		} else {
+			&mut *used
+		};
+		used.entry((model_type.to_string(), key.properties().clone()))
+			.or_insert_with(HashSet::new)
+			.insert(value);
+	}
+}
+
+fn generate_model_data(
+	model_type: String,
+	model: &Model,
+	ctx: &mut GenContext,
+	spec: &Specification,
+	used: &mut UsedKeys,
+	rng: &mut StdRng,
+) -> Result<(), String> {
+	let mut model_data = generate_row(&model_type, model, spec.locale(), ctx, rng)?;
+	let mut attempts = 1;
+	while let Some(key) = repeated_key(&model_type, model, &model_data, ctx, used) {
+		if attempts >= MAX_UNIQUE_ATTEMPTS {
+			return Err(format!(
+				"Could not generate a unique `{}` for `{}` after {} attempts, as too few distinct values are possible",
+				key.properties().join("`, `"),
+				model_type,
+				attempts
+			));
+		}
+		model_data = generate_row(&model_type, model, spec.locale(), ctx, rng)?;
+		attempts += 1;
+	}
+
+	use_keys(&model_type, model, &model_data, ctx, used);
+	ctx.add_model_data(model_type.clone(), model_data.clone());
+
+	let child_models = model
+		.type_iter()
+		.filter_map(|(property, data_type)| {
+			child_model_name(data_type).map(|name| (Some(name.clone()), (property, data_type)))
+		})
+		.collect();
+
+	for (_, child_type) in order_by_foreign_keys(child_models, spec) {
+		let relation = match child_type.element_type() {
+			DT::Model(relation) => relation,
+			_ => continue,
+		};
+		let gen_name = relation.name().clone();
+
+		// Stop following a recursive relation once the model is nested deep enough inside itself
+		if let Some(max_depth) = relation.max_depth() {
+			let own = if gen_name == model_type { 1 } else { 0 };
+			if ctx.ancestor_count(&gen_name) + own > max_depth {
+				continue;
+			}
+		}
+
+		if roll_null(child_type, rng) {
+			continue;
+		}
+		let (iterations, item_type) = match child_type.non_null() {
+			DT::List(nested) => (nested.length(rng), nested.item_type()),
+			other => (1, other),
+		};
+		let next_model = match spec.get_definition(&gen_name) {
+			Some(next_model) => next_model,
+			None => continue,
+		};
+
+		let mut index = 0;
+		let mut sibling_keys = UsedKeys::new();
 		for _ in 0..iterations {
+			if roll_null(item_type, rng) {
+				continue;
+			}
 			let mut next_model_ctx = GenContext {
 				parent_context: Some(Box::new(ctx.clone())),
 				parent_model: Some(GenData {
+					model_type: model_type.clone(),
 					model: model.clone(),
 					data: model_data.clone(),
 				}),
 				models: HashMap::new(),
+				index,
+				sibling_keys,
 			};
 			generate_model_data(
 				gen_name.clone(),
-				&spec.get_definition(&gen_name),
+				next_model,
 				&mut next_model_ctx,
 				&spec,
-			);
+				used,
+				rng,
+			)?;
+			index += 1;
+			sibling_keys = next_model_ctx.sibling_keys;
 			ctx.merge_model_data(&mut next_model_ctx.models);
 		}
-	});
+	}
+
+	Ok(())
+}
+
+/// How array values, such as those generated by a `List` of `RandomData`, are written into a single
+/// CSV field
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ListFormat {
+	/// Write the array as JSON, e.g. `["a","b"]`
+	JSON,
+	/// Write the array as a Postgres array literal, e.g. `{"a","b"}`
+	Postgres,
+	/// Write each item as plain text, separated by the given delimiter, e.g. `a;b`
+	Delimited(String),
+}
+
+/// Options that control how CSV output is written
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CsvOptions {
+	pub list_format: ListFormat,
+	/// Whether the first row of each file names the columns
+	pub header: bool,
+	/// The byte that separates each field in a row
+	pub delimiter: u8,
+	pub quote_style: QuoteStyle,
+	pub line_terminator: LineTerminator,
+	/// The text written into a field when a value is `null` or absent
+	pub null_value: String,
+}
+
+impl Default for CsvOptions {
+	fn default() -> Self {
+		CsvOptions {
+			list_format: ListFormat::JSON,
+			header: true,
+			delimiter: b',',
+			quote_style: QuoteStyle::Necessary,
+			line_terminator: LineTerminator::LF,
+			null_value: String::from("null"),
+		}
+	}
+}
+
+/// When fields in CSV output are wrapped in quotes
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum QuoteStyle {
+	/// Quote every field
+	Always,
+	/// Only quote fields that contain a delimiter, quote or line break
+	Necessary,
+	/// Quote every field that is not a number
+	NonNumeric,
+	/// Never quote fields, even where this produces an invalid file
+	Never,
+}
+
+/// The line ending written after each row of CSV output
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum LineTerminator {
+	LF,
+	CRLF,
+}
+
+/// Quote and escape a single item of a Postgres array literal. Nested arrays become nested literals
+fn postgres_array_item(value: &Value) -> String {
+	match value {
+		Value::Null => String::from("NULL"),
+		Value::Bool(_) | Value::Number(_) => value.to_string(),
+		Value::Array(items) => postgres_array(items),
+		other => format!(
+			"\"{}\"",
+			plain_text(other).replace('\\', "\\\\").replace('"', "\\\"")
+		),
+	}
+}
+
+fn postgres_array(items: &[Value]) -> String {
+	let items: Vec<String> = items.iter().map(postgres_array_item).collect();
+	format!("{{{}}}", items.join(","))
+}
+
+/// Render a generated value as the contents of a single CSV field
+fn csv_text(value: &Value, options: &CsvOptions) -> String {
+	match (value, &options.list_format) {
+		(Value::Null, _) => options.null_value.clone(),
+		(Value::Array(items), ListFormat::Postgres) => postgres_array(items),
+		(Value::Array(items), ListFormat::Delimited(delimiter)) => {
+			let items: Vec<String> = items.iter().map(plain_text).collect();
+			items.join(delimiter)
+		}
+		_ => plain_text(value),
+	}
+}
+
+/// The SQL dialect that is used to quote and escape values in SQL output
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum SqlDialect {
+	Postgres,
+	MySQL,
+	SQLite,
+}
+
+/// Options that control how SQL output is written
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SqlOptions {
+	pub dialect: SqlDialect,
+	/// The maximum number of rows inserted by a single `INSERT` statement
+	pub batch_size: usize,
+}
+
+impl Default for SqlOptions {
+	fn default() -> Self {
+		SqlOptions {
+			dialect: SqlDialect::Postgres,
+			batch_size: 500,
+		}
+	}
+}
+
+impl SqlDialect {
+	fn quote_identifier(&self, identifier: &str) -> String {
+		match self {
+			SqlDialect::MySQL => format!("`{}`", identifier.replace('`', "``")),
+			SqlDialect::Postgres | SqlDialect::SQLite => {
+				format!("\"{}\"", identifier.replace('"', "\"\""))
+			}
+		}
+	}
+
+	fn quote_string(&self, content: &str) -> String {
+		match self {
+			SqlDialect::MySQL => format!("'{}'", content.replace('\\', "\\\\").replace('\'', "''")),
+			SqlDialect::Postgres | SqlDialect::SQLite => {
+				format!("'{}'", content.replace('\'', "''"))
+			}
+		}
+	}
+
+	/// Render a generated value as an SQL literal
+	fn literal(&self, value: &Value) -> String {
+		match (value, self) {
+			(Value::Null, _) => String::from("NULL"),
+			(Value::Bool(flag), SqlDialect::SQLite) => String::from(if *flag { "1" } else { "0" }),
+			(Value::Bool(flag), _) => String::from(if *flag { "TRUE" } else { "FALSE" }),
+			(Value::Number(number), _) => number.to_string(),
+			(Value::String(content), _) => self.quote_string(content),
+			(Value::Array(items), SqlDialect::Postgres) => {
+				self.quote_string(&postgres_array(items))
+			}
+			(other, _) => self.quote_string(&other.to_string()),
+		}
+	}
+}
+
+/// Find the models that must be inserted before the given model: the models that contain it as a
+/// `Model` or `List` property, and the models that it points at through a `ForeignKey`
+fn insertion_dependencies(
+	model_type: &str,
+	spec: &Specification,
+	candidates: &[String],
+) -> Vec<String> {
+	let mut dependencies: Vec<String> = candidates
+		.iter()
+		.filter(|candidate| {
+			spec.get_definition(candidate).map_or(false, |model| {
+				get_model_children(model)
+					.iter()
+					.any(|child| child == model_type)
+			})
+		})
+		.cloned()
+		.collect();
+
+	if let Some(model) = spec.get_definition(model_type) {
+		for (_, data_type) in model.type_iter() {
+			if let DT::ForeignKey { model, .. } = data_type.element_type() {
+				dependencies.push(model.clone());
+			}
+		}
+	}
+
+	dependencies.retain(|dependency| dependency != model_type);
+	dependencies
+}
+
+/// Order the generated models so that parent tables are inserted before their children, and the
+/// targets of foreign keys before the models that point at them. Models are otherwise ordered by
+/// name, and any cycle is broken at the first model by name
+fn insertion_order(data: &ModelDataMap, spec: &Specification) -> Vec<String> {
+	let mut remaining: Vec<String> = data.keys().cloned().collect();
+	remaining.sort();
+
+	let mut ordered = Vec::with_capacity(remaining.len());
+	while !remaining.is_empty() {
+		let next = remaining
+			.iter()
+			.position(|model_type| {
+				insertion_dependencies(model_type, spec, &remaining)
+					.iter()
+					.all(|dependency| !remaining.contains(dependency))
+			})
+			.unwrap_or(0);
+		ordered.push(remaining.remove(next));
+	}
+	ordered
+}
+
+/// Write every instance of a model as a row of CSV, preceded by a header row naming the columns
+/// unless it has been disabled
+fn write_csv_rows<W: Write>(
+	out: W,
+	model_list: &[ModelData],
+	columns: &[String],
+	options: &CsvOptions,
+) -> csv::Result<()> {
+	let mut writer = WriterBuilder::new()
+		.delimiter(options.delimiter)
+		.quote_style(match options.quote_style {
+			QuoteStyle::Always => CsvQuoteStyle::Always,
+			QuoteStyle::Necessary => CsvQuoteStyle::Necessary,
+			QuoteStyle::NonNumeric => CsvQuoteStyle::NonNumeric,
+			QuoteStyle::Never => CsvQuoteStyle::Never,
+		})
+		.terminator(match options.line_terminator {
+			LineTerminator::LF => Terminator::Any(b'\n'),
+			LineTerminator::CRLF => Terminator::CRLF,
+		})
+		.from_writer(out);
+
+	if options.header {
+		writer.write_record(columns)?;
+	}
+	for data_set in model_list {
+		let row: Vec<String> = columns
+			.iter()
+			.map(|column| {
+				data_set
+					.get(column)
+					.map_or(options.null_value.clone(), |v| csv_text(v, options))
+			})
+			.collect();
+		writer.write_record(&row)?;
+	}
+	writer.flush()?;
+
+	Ok(())
+}
+
+/// Write every instance of a model as a series of batched `INSERT` statements
+fn write_sql_inserts<W: Write>(
+	out: &mut W,
+	type_name: &str,
+	model_list: &[ModelData],
+	columns: &[String],
+	options: &SqlOptions,
+) -> std::io::Result<()> {
+	let dialect = options.dialect;
+	let column_list: Vec<String> = columns
+		.iter()
+		.map(|c| dialect.quote_identifier(c))
+		.collect();
+
+	for batch in model_list.chunks(options.batch_size.max(1)) {
+		writeln!(
+			out,
+			"INSERT INTO {} ({}) VALUES",
+			dialect.quote_identifier(type_name),
+			column_list.join(", ")
+		)?;
+		for (index, data_set) in batch.iter().enumerate() {
+			let values: Vec<String> = columns
+				.iter()
+				.map(|column| {
+					data_set
+						.get(column)
+						.map_or(String::from("NULL"), |v| dialect.literal(v))
+				})
+				.collect();
+			let terminator = if index + 1 == batch.len() { ";" } else { "," };
+			writeln!(out, "\t({}){}", values.join(", "), terminator)?;
+		}
+	}
+
+	Ok(())
+}
+
+/// The name used for the output file and SQL table of a model. The `::` that separates a namespace
+/// from a model name can not be used in either, so it is written as `__`, as in `common__user`
+pub fn output_name(type_name: &str) -> String {
+	type_name.replace(NAMESPACE_SEPARATOR, "__")
 }
 
 pub fn write_output(
@@ -239,38 +968,29 @@ pub fn write_output(
 	spec: Specification,
 	out_type: OutputType,
 	pretty: bool,
+	csv_options: &CsvOptions,
+	sql_options: &SqlOptions,
 ) {
 	create_dir_all(&folder);
 	match out_type {
 		OutputType::CSV => data.iter().for_each(|(type_name, model_list)| {
 			let mut path = PathBuf::from(&folder);
-			path.push(&type_name);
+			path.push(output_name(type_name));
 			path = path.with_extension(out_type.as_extension());
 
-			let mut file = File::create(path).unwrap();
-			let ordering = spec.get_serialize_ref(&type_name);
-			let mut writer = Csv::from_writer(file);
-			for data_set in model_list {
-				let mut row: Vec<String> = Vec::new();
-				if let Some(order) = ordering {
-					for key in order.iter() {
-						row.push(
-							data_set
-								.get(key)
-								.unwrap_or(&String::from("null"))
-								.to_string(),
-						);
-					}
-				} else {
-					data_set.values().for_each(|v| row.push(v.clone()));
-				}
-				writer.write_record(&row).unwrap();
-			}
+			let file = File::create(path).unwrap();
+			write_csv_rows(
+				file,
+				model_list,
+				&spec.column_order(&type_name),
+				csv_options,
+			)
+			.expect("Write model data to file");
 		}),
 		OutputType::JSON => {
 			data.iter().for_each(|(type_name, model_list)| {
 				let mut path = PathBuf::from(&folder);
-				path.push(&type_name);
+				path.push(output_name(type_name));
 				path = path.with_extension(out_type.as_extension());
 				let mut file = File::create(path).expect("Creating file");
 
@@ -285,5 +1005,429 @@ pub fn write_output(
 				file.flush().expect("Flush file contents");
 			});
 		}
+		OutputType::NDJSON => {
+			data.iter().for_each(|(type_name, model_list)| {
+				let mut path = PathBuf::from(&folder);
+				path.push(output_name(type_name));
+				path = path.with_extension(out_type.as_extension());
+				let mut writer = BufWriter::new(File::create(path).expect("Creating file"));
+
+				for data_set in model_list {
+					serde_json::to_writer(&mut writer, data_set).expect("Serialising model");
+					writer.write_all(b"\n").expect("Write model data to file");
+				}
+				writer.flush().expect("Flush file contents");
+			});
+		}
+		OutputType::SQL => {
+			// Files are numbered so that running them in name order satisfies foreign key constraints
+			for (position, type_name) in insertion_order(&data, &spec).iter().enumerate() {
+				let mut path = PathBuf::from(&folder);
+				path.push(format!("{:03}_{}", position + 1, output_name(type_name)));
+				path = path.with_extension(out_type.as_extension());
+				let mut file = File::create(path).expect("Creating file");
+
+				write_sql_inserts(
+					&mut file,
+					&output_name(type_name),
+					&data[type_name],
+					&spec.column_order(type_name),
+					sql_options,
+				)
+				.expect("Write model data to file");
+				file.flush().expect("Flush file contents");
+			}
+		}
 	}
 }
+
+#[test]
+fn seeded_generation_is_repeatable() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"serialize": {},
+			"models": {
+				"user": {
+					"id": { "type": "RandomData", "value": "UUID4" },
+					"age": { "type": "RandomData", "value": { "NumberBetween": { "min": 18, "max": 99 } } }
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let first = from_spec(String::from("user"), spec.clone(), 10, Some(42)).unwrap();
+	let second = from_spec(String::from("user"), spec, 10, Some(42)).unwrap();
+	assert_eq!(first, second);
+}
+
+#[test]
+fn csv_list_formats() {
+	let value: Value = serde_json::from_str(r#"["a", "say \"hi\"", 3, null]"#).unwrap();
+	let postgres = CsvOptions {
+		list_format: ListFormat::Postgres,
+		..CsvOptions::default()
+	};
+	let delimited = CsvOptions {
+		list_format: ListFormat::Delimited(String::from(";")),
+		..CsvOptions::default()
+	};
+
+	assert_eq!(
+		csv_text(&value, &CsvOptions::default()),
+		r#"["a","say \"hi\"",3,null]"#
+	);
+	assert_eq!(csv_text(&value, &postgres), r#"{"a","say \"hi\"",3,NULL}"#);
+	assert_eq!(csv_text(&value, &delimited), r#"a;say "hi";3;null"#);
+}
+
+#[test]
+fn foreign_keys_point_at_generated_siblings() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"serialize": {},
+			"models": {
+				"forum": {
+					"comments": { "type": "List", "value": { "type": "Model", "value": "comment" } },
+					"users": { "type": "List", "value": { "type": "Model", "value": "user" } }
+				},
+				"user": {
+					"id": { "type": "RandomData", "value": "UUID4" }
+				},
+				"comment": {
+					"author_id": {
+						"type": "ForeignKey",
+						"value": { "model": "user", "property": "id", "selection": "RoundRobin" }
+					}
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let data = from_spec(String::from("forum"), spec, 1, Some(1)).unwrap();
+	let user_ids: Vec<&Value> = data["user"].iter().map(|user| &user["id"]).collect();
+	let author_ids: Vec<&Value> = data["comment"]
+		.iter()
+		.map(|comment| &comment["author_id"])
+		.collect();
+	assert_eq!(user_ids, author_ids);
+
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"models": {
+				"user": { "id": "UUID4" },
+				"post": { "author": { "type": "ForeignKey", "value": { "model": "user", "property": "id" } } }
+			}
+		}"#,
+	)
+	.unwrap();
+	assert_eq!(
+		from_spec(String::from("post"), spec, 2, None).unwrap_err(),
+		"`post.author`: ForeignKey points at `user`, which has no generated instances"
+	);
+}
+
+#[test]
+fn planned_roots_are_generated_before_roots_that_point_at_them() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"models": {
+				"user": { "id": "UUID4" },
+				"post": { "author": { "type": "ForeignKey", "value": { "model": "user", "property": "id" } } }
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let mut plan = RunPlan::new();
+	plan.add_model("post", 5).add_model("user", 3);
+	let data = from_plan(&plan, spec, Some(1)).unwrap();
+	let user_ids: Vec<&Value> = data["user"].iter().map(|user| &user["id"]).collect();
+	assert_eq!(user_ids.len(), 3);
+	assert_eq!(data["post"].len(), 5);
+	assert!(data["post"]
+		.iter()
+		.all(|post| user_ids.contains(&&post["author"])));
+}
+
+#[test]
+fn recursive_models_stop_at_max_depth() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"serialize": {},
+			"models": {
+				"comment": {
+					"id": { "type": "RandomData", "value": "UUID4" },
+					"replies": {
+						"type": "List",
+						"value": {
+							"of": { "type": "Model", "value": { "model": "comment", "max_depth": 2 } },
+							"count": 2
+						}
+					}
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let data = from_spec(String::from("comment"), spec, 1, Some(1)).unwrap();
+	assert_eq!(data["comment"].len(), 1 + 2 + 4);
+}
+
+#[test]
+fn nullable_properties_are_sometimes_null() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"models": {
+				"user": {
+					"phone": { "type": "RandomData", "value": "PhoneNumber", "nullable": 0.5 },
+					"email": { "type": "RandomData", "value": "Email", "nullable": 1 },
+					"avatar": { "type": "Model", "value": "image", "nullable": 1 }
+				},
+				"image": { "url": "UUID4" }
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let data = from_spec(String::from("user"), spec, 100, Some(3)).unwrap();
+	let users = &data["user"];
+	assert!(users.iter().all(|user| user["email"].is_null()));
+	assert!(users.iter().any(|user| user["phone"].is_null()));
+	assert!(users.iter().any(|user| user["phone"].is_string()));
+	assert!(!data.contains_key("image"));
+}
+
+#[test]
+fn templates_compose_other_values() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"models": {
+				"company": {
+					"code": { "String": { "content": "ACME" } },
+					"staff": ["@employee"]
+				},
+				"employee": {
+					"first": { "String": { "content": "Ada" } },
+					"last": { "String": { "content": "Lovelace" } },
+					"handle": "{email}",
+					"email": "{first}.{last}@{^.code}.test",
+					"badge": {
+						"type": "Template",
+						"value": {
+							"template": "{^.code}-{n:04}-{n}/{missing}{{{UUID4}}}",
+							"values": {
+								"n": { "NumberBetween": { "min": 1, "max": 100 } },
+								"missing": { "type": "RandomData", "value": "NullValue" }
+							}
+						}
+					}
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let data = from_spec(String::from("company"), spec, 2, Some(9)).unwrap();
+	let badge = regex::Regex::new(r"^ACME-00(\d\d)-(\d+)/\{[0-9a-f-]{36}\}$").unwrap();
+	for employee in &data["employee"] {
+		assert_eq!(employee["email"], "Ada.Lovelace@ACME.test");
+		assert_eq!(employee["handle"], employee["email"]);
+		let badge = badge.captures(employee["badge"].as_str().unwrap()).unwrap();
+		assert_eq!(badge[1].trim_start_matches('0'), &badge[2]);
+	}
+}
+
+#[test]
+fn sequences_count_instances_globally_or_per_parent() {
+	let spec: Specification = serde_json::from_str(
+		r##"{
+			"models": {
+				"order": {
+					"id": { "type": "Sequence", "value": {} },
+					"lines": { "type": "List", "value": { "of": "@line", "count": 3 } }
+				},
+				"line": {
+					"id": { "type": "Sequence", "value": { "start": 100, "step": 10 } },
+					"position": "#",
+					"number": { "type": "Sequence", "value": { "scope": "Parent" } },
+					"label": "{^.id}/{#}"
+				}
+			}
+		}"##,
+	)
+	.unwrap();
+
+	let data = from_spec(String::from("order"), spec, 2, None).unwrap();
+	let ids: Vec<&Value> = data["order"].iter().map(|order| &order["id"]).collect();
+	assert_eq!(ids, vec![1, 2]);
+
+	let lines: Vec<(i64, i64, i64, &str)> = data["line"]
+		.iter()
+		.map(|line| {
+			(
+				line["id"].as_i64().unwrap(),
+				line["position"].as_i64().unwrap(),
+				line["number"].as_i64().unwrap(),
+				line["label"].as_str().unwrap(),
+			)
+		})
+		.collect();
+	assert_eq!(
+		lines,
+		vec![
+			(100, 0, 1, "1/0"),
+			(110, 1, 2, "1/1"),
+			(120, 2, 3, "1/2"),
+			(130, 0, 1, "2/0"),
+			(140, 1, 2, "2/1"),
+			(150, 2, 3, "2/2"),
+		]
+	);
+}
+
+#[test]
+fn unique_properties_are_not_repeated() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"models": {
+				"shop": {
+					"code": { "NumberBetween": { "min": 0, "max": 20 }, "unique": true },
+					"shelves": { "type": "List", "value": { "of": "@shelf", "count": 5 } }
+				},
+				"shelf": {
+					"aisle": { "NumberBetween": { "min": 0, "max": 2 } },
+					"side": { "OneOf": { "values": ["left", "right", "middle"] } },
+					"slot": { "NumberBetween": { "min": 0, "max": 5 }, "unique": "Parent" },
+					"unique": [{ "properties": ["aisle", "side"], "scope": "Parent" }]
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let data = from_spec(String::from("shop"), spec.clone(), 20, Some(5)).unwrap();
+	let mut codes: Vec<i64> = data["shop"]
+		.iter()
+		.map(|shop| shop["code"].as_i64().unwrap())
+		.collect();
+	codes.sort();
+	assert_eq!(codes, (0..20).collect::<Vec<i64>>());
+	for shelves in data["shelf"].chunks(5) {
+		let mut slots: Vec<i64> = shelves
+			.iter()
+			.map(|shelf| shelf["slot"].as_i64().unwrap())
+			.collect();
+		slots.sort();
+		assert_eq!(slots, vec![0, 1, 2, 3, 4]);
+
+		let mut pairs: Vec<String> = shelves
+			.iter()
+			.map(|shelf| format!("{}{}", shelf["aisle"], shelf["side"]))
+			.collect();
+		pairs.sort();
+		pairs.dedup();
+		assert_eq!(pairs.len(), 5);
+	}
+
+	let error = from_spec(String::from("shop"), spec, 21, Some(5)).unwrap_err();
+	assert!(error.starts_with("Could not generate a unique `code` for `shop`"));
+}
+
+#[test]
+fn sql_inserts_are_batched_and_escaped() {
+	let rows: Vec<ModelData> = serde_json::from_str(
+		r#"[
+			{ "id": 1, "name": "O'Brien", "active": true },
+			{ "id": 2, "name": null, "active": false },
+			{ "id": 3, "active": true }
+		]"#,
+	)
+	.unwrap();
+	let options = SqlOptions {
+		dialect: SqlDialect::Postgres,
+		batch_size: 2,
+	};
+
+	let mut out = Vec::new();
+	let columns = vec![
+		String::from("active"),
+		String::from("id"),
+		String::from("name"),
+	];
+	write_sql_inserts(&mut out, "user", &rows, &columns, &options).unwrap();
+	assert_eq!(
+		String::from_utf8(out).unwrap(),
+		"INSERT INTO \"user\" (\"active\", \"id\", \"name\") VALUES\n\
+		 \t(TRUE, 1, 'O''Brien'),\n\
+		 \t(FALSE, 2, NULL);\n\
+		 INSERT INTO \"user\" (\"active\", \"id\", \"name\") VALUES\n\
+		 \t(TRUE, 3, NULL);\n"
+	);
+}
+
+#[test]
+fn namespaced_models_are_written_with_safe_names() {
+	let mut spec: Specification =
+		serde_json::from_str(r#"{ "models": { "post": { "author": "@common::user" } } }"#).unwrap();
+	let common = r#"{ "models": { "user": { "id": "UUID4" } } }"#;
+	spec.merge(serde_json::from_str(common).unwrap(), Some("common"))
+		.unwrap();
+	let data = from_spec(String::from("post"), spec.clone(), 2, Some(1)).unwrap();
+
+	let folder = std::env::temp_dir().join(format!("mockery-namespaces-{}", std::process::id()));
+	let write = |out_type| {
+		write_output(
+			&folder,
+			data.clone(),
+			spec.clone(),
+			out_type,
+			false,
+			&CsvOptions::default(),
+			&SqlOptions::default(),
+		)
+	};
+	write(OutputType::CSV);
+	write(OutputType::SQL);
+
+	assert!(folder.join("common__user.csv").is_file());
+	let inserts = read_to_string(folder.join("002_common__user.sql")).unwrap();
+	assert!(inserts.starts_with("INSERT INTO \"common__user\" (\"id\") VALUES"));
+	std::fs::remove_dir_all(&folder).unwrap();
+}
+
+#[test]
+fn csv_rows_follow_declaration_order_with_header() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"serialize": {},
+			"models": {
+				"user": {
+					"name": { "type": "RandomData", "value": { "String": { "content": "Ada" } } },
+					"age": { "type": "RandomData", "value": "NullValue" },
+					"id": { "type": "RandomData", "value": { "String": { "content": "1" } } }
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+	let data = from_spec(String::from("user"), spec.clone(), 1, None).unwrap();
+	let options = CsvOptions {
+		delimiter: b';',
+		null_value: String::new(),
+		line_terminator: LineTerminator::CRLF,
+		..CsvOptions::default()
+	};
+
+	let mut out = Vec::new();
+	write_csv_rows(
+		&mut out,
+		&data["user"],
+		&spec.column_order("user"),
+		&options,
+	)
+	.unwrap();
+	assert_eq!(String::from_utf8(out).unwrap(), "name;age;id\r\nAda;;1\r\n");
+}
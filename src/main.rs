@@ -5,7 +5,10 @@ fn main() {
 	let args = cli::get_args_from_stdin();
 	match process_args(args) {
 		Ok(_) => (),
-		Err(e) => eprintln!("{}", e),
+		Err(e) => {
+			eprintln!("{}", e);
+			std::process::exit(1);
+		}
 	}
 }
 
@@ -17,30 +20,68 @@ enum StringErrorCompat {
 }
 
 fn process_args(args: cli::CliArgs) -> Result<(), Error> {
-	use mockery::specification::{self, Specification};
+	use mockery::specification::{self, RunPlan, Specification};
 	extern crate serde_json;
 
-	let model_name = &args.model_name;
-	let spec = specification::io::read_spec(&args.gen_spec_path)?;
-
-	if spec.has_model(&model_name) {
-		let data = generator::from_spec(model_name.clone(), spec.clone(), args.model_amount)
-			.map_err(|e| StringErrorCompat::S(e))?;
-
-		generator::write_output(
-			&args.output_path,
-			data,
-			spec,
-			args.output_type,
-			args.pretty_print,
-		);
-	} else {
-		println!(
-			"No such model {} in {:?}",
-			&model_name,
-			&args.gen_spec_path.to_str()
-		);
+	let spec = match args.spec_format {
+		Some(format) => specification::io::read_spec_as(&args.gen_spec_path, format)?,
+		None => specification::io::read_spec(&args.gen_spec_path)?,
+	};
+
+	if args.validate_only {
+		return match spec.validate() {
+			Ok(()) => {
+				println!("{:?} is a valid spec", &args.gen_spec_path);
+				Ok(())
+			}
+			Err(errors) => {
+				for error in &errors {
+					println!("{}", error);
+				}
+				Err(StringErrorCompat::S(format!(
+					"Found {} problem(s) in {:?}",
+					errors.len(),
+					&args.gen_spec_path
+				))
+				.into())
+			}
+		};
+	}
+
+	let mut plan = match &args.plan_path {
+		Some(plan_path) => specification::io::read_plan(plan_path)?,
+		None => RunPlan::new(),
+	};
+	for (model_name, quantity) in &args.plan_models {
+		plan.add_model(model_name, *quantity);
+	}
+	if !args.model_name.is_empty() {
+		plan.add_model(&args.model_name, args.model_amount);
 	}
 
+	for (model_name, _) in plan.model_iter() {
+		if !spec.has_model(model_name) {
+			println!(
+				"No such model {} in {:?}",
+				model_name,
+				&args.gen_spec_path.to_str()
+			);
+			return Ok(());
+		}
+	}
+
+	let data = generator::from_plan(&plan, spec.clone(), args.seed)
+		.map_err(|e| StringErrorCompat::S(e))?;
+
+	generator::write_output(
+		&args.output_path,
+		data,
+		spec,
+		args.output_type,
+		args.pretty_print,
+		&args.csv_options,
+		&args.sql_options,
+	);
+
 	Ok(())
 }
@@ -1,8 +1,16 @@
-use crate::datatypes::RandomData;
+use crate::datatypes::{Locale, RandomData};
 use failure::Fail;
+use rand::Rng;
+use std::fmt;
 use std::iter::Iterator;
 use std::string::ToString;
 
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::de::{
+	DeserializeSeed, Deserializer, Error as DeError, IntoDeserializer, MapAccess, SeqAccess,
+	Visitor,
+};
 use serde_derive::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -11,61 +19,1863 @@ pub struct Reference {
 	property: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The definition of a single property of a model. Each property can be written out in full as a
+/// map of its `type` to its `value`, or in one of the shorthand forms:
+///
+/// - `"UUID4"` or `{ "NumberBetween": { "min": 1, "max": 10 } }` for `RandomData`
+/// - `"@user"` for a child `Model`
+/// - `["@template"]` or `["Email"]` for a `List` of the given item
+/// - `"^.id"` or `"^~^.id"` for a `Reference` to the property after the `.`
+/// - `"#"` for the position of the model in its parent's list, counting from 0, as a `Sequence`
+/// - `"{first_name}.{last_name}@acme.test"`, or any other text containing a `{`, for a `Template`
+///
+/// Any property written as a map can also take a `nullable` probability, which makes it `null` that
+/// proportion of the time, as a shorthand for wrapping it in a `Nullable`. Similarly, `unique` can be
+/// set to `true` or to a `UniqueScope` as a shorthand for wrapping the property in a `Unique`, and
+/// `locale` as a shorthand for wrapping it in a `Localized`.
+///
+/// # Examples
+///
+/// ```json
+/// {
+///     "id": "UUID4",
+///     "id_in_full": { "type": "RandomData", "value": "UUID4" },
+///     "author": "@user",
+///     "templates": ["@template"],
+///     "site_id": "^.id",
+///     "deleted_at": { "type": "RandomData", "value": { "DateTime": {} }, "nullable": 0.9 }
+/// }
+/// ```
+#[derive(Debug, Serialize, Clone)]
 #[serde(tag = "type", content = "value")]
 pub enum DataType {
 	RandomData(RandomData),
-	List(Box<DataType>),
-	Model(String),
-	Reference { path: String, property: String },
+	List(ListDefinition),
+	Model(ModelRelation),
+	Reference {
+		path: String,
+		property: String,
+	},
+	/// Copies a property from an existing instance of any model that has already been generated,
+	/// rather than from an ancestor. The instance is chosen according to the `selection` mode,
+	/// which defaults to `Random`. Generation fails if no instance of the model exists yet
+	///
+	/// # Examples
+	///
+	/// ```json
+	/// {
+	///     "type": "ForeignKey",
+	///     "value": {
+	///         "model": "user",
+	///         "property": "id",
+	///         "selection": { "Weighted": { "property": "followers" } }
+	///     }
+	/// }
+	/// ```
+	ForeignKey {
+		model: String,
+		property: String,
+		selection: Option<Selection>,
+	},
+	/// Generates `null` with the given probability, and a value of the wrapped data type otherwise.
+	/// A child model that is `null` is not generated at all
+	Nullable {
+		of: Box<DataType>,
+		/// The chance of generating `null`, between 0 and 1
+		probability: f64,
+	},
+	/// Builds a string from other values, so that derived properties stay consistent with the rest
+	/// of the model
+	///
+	/// # Examples
+	///
+	/// ```json
+	/// {
+	///     "type": "Template",
+	///     "value": "{first_name}.{last_name}@acme.test"
+	/// }
+	/// ```
+	Template(TemplateDefinition),
+	/// Generates a sequence of integers, such as `1, 2, 3` for a primary key. Each value is worked
+	/// out from the position of the instance being generated rather than from a shared counter, so
+	/// the sequence is the same however generation is scheduled
+	///
+	/// # Examples
+	///
+	/// ```json
+	/// {
+	///     "type": "Sequence",
+	///     "value": { "start": 100, "step": 10, "scope": "Parent" }
+	/// }
+	/// ```
+	Sequence {
+		/// The first value of the sequence. Defaults to 1
+		start: Option<i64>,
+		/// The difference between each value and the next. Defaults to 1
+		step: Option<i64>,
+		/// Which instances of the model share the sequence. Defaults to `Global`
+		scope: Option<SequenceScope>,
+	},
+	/// Generates a value of the wrapped data type that no other instance of the model in the same
+	/// scope has, which defaults to `Model`. Values are generated again until one is found, giving
+	/// up with an error once there are too few possible values left. This can only be used for a
+	/// whole property
+	Unique {
+		of: Box<DataType>,
+		scope: Option<UniqueScope>,
+	},
+	/// Generates the wrapped data type in the given locale, rather than the locale of the
+	/// specification
+	Localized {
+		of: Box<DataType>,
+		locale: Locale,
+	},
+}
+
+/// Which instances of a model count towards a `Sequence`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum SequenceScope {
+	/// Every instance of the model, in the order they are generated
+	Global,
+	/// The instances generated by the same property of the same parent, in the order they appear in
+	/// its list. Root models count the instances asked for in the plan
+	Parent,
+}
+
+impl Default for SequenceScope {
+	fn default() -> Self {
+		SequenceScope::Global
+	}
+}
+
+/// The first value of a `Sequence` that does not specify one
+pub const DEFAULT_SEQUENCE_START: i64 = 1;
+
+/// Which instances of a model must not share a value for a `Unique` property or `UniqueKey`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum UniqueScope {
+	/// Every instance of the model
+	Model,
+	/// The instances of the model that belong to the same parent
+	Parent,
+}
+
+impl Default for UniqueScope {
+	fn default() -> Self {
+		UniqueScope::Model
+	}
+}
+
+/// A set of properties whose values, taken together, must not be repeated by two instances of a
+/// model. This can either be written as the list of property names, or as a map containing them
+/// under `properties` alongside a `scope`. Instances where any of the properties is `null` or
+/// missing are not checked
+///
+/// # Examples
+///
+/// ```json
+/// {
+///     "unique": [
+///         ["first_name", "last_name"],
+///         { "properties": ["position"], "scope": "Parent" }
+///     ]
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum UniqueKey {
+	Scoped {
+		properties: Vec<String>,
+		scope: Option<UniqueScope>,
+	},
+	Properties(Vec<String>),
 }
 
-use std::collections::{hash_map::Iter, HashMap};
+impl UniqueKey {
+	pub fn properties(&self) -> &Vec<String> {
+		match self {
+			UniqueKey::Scoped { properties, .. } => properties,
+			UniqueKey::Properties(properties) => properties,
+		}
+	}
+
+	pub fn scope(&self) -> UniqueScope {
+		match self {
+			UniqueKey::Scoped { scope, .. } => scope.unwrap_or_default(),
+			UniqueKey::Properties(_) => UniqueScope::default(),
+		}
+	}
+}
+
+/// The `unique` shorthand on a property, which is either a flag or the scope to use
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum UniqueFlag {
+	Enabled(bool),
+	Scope(UniqueScope),
+}
+
+/// How a `ForeignKey` chooses between the existing instances of the model it points at
 #[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum Selection {
+	/// Any instance, with equal probability
+	Random,
+	/// Each instance in turn, in the order they were generated, starting again from the first once
+	/// all have been used
+	RoundRobin,
+	/// Any instance, with a probability proportional to the numeric value of the given property on
+	/// that instance
+	Weighted { property: String },
+}
+
+impl Default for Selection {
+	fn default() -> Self {
+		Selection::Random
+	}
+}
+
+/// The model generated by a `Model` property. This can either be written as the name of the model,
+/// or as a map containing the name under `model` alongside a `max_depth`. A model that contains
+/// itself, directly or through other models, must set a `max_depth` on at least one of the
+/// relations that form the cycle.
+///
+/// # Examples
+///
+/// A comment thread where replies are nested at most 3 comments deep:
+///
+/// ```json
+/// {
+///     "type": "List",
+///     "value": {
+///         "of": { "type": "Model", "value": { "model": "comment", "max_depth": 3 } },
+///         "max": 2
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Clone)]
+#[serde(untagged)]
+pub enum ModelRelation {
+	Bounded {
+		model: String,
+		/// The number of times the model may be nested inside itself by following this relation.
+		/// Once it has been reached, the property is left out, or left empty for a `List`
+		max_depth: usize,
+	},
+	Named(String),
+}
+
+impl ModelRelation {
+	/// The name of the model that is generated
+	pub fn name(&self) -> &String {
+		match self {
+			ModelRelation::Bounded { model, .. } => model,
+			ModelRelation::Named(model) => model,
+		}
+	}
+
+	pub fn max_depth(&self) -> Option<usize> {
+		match self {
+			ModelRelation::Bounded { max_depth, .. } => Some(*max_depth),
+			ModelRelation::Named(_) => None,
+		}
+	}
+}
+
+/// The number of items generated for a list that does not specify its own length
+pub const DEFAULT_LIST_LENGTH: usize = 5;
+
+/// The definition of the items in a `List` property. This can either be written as the item
+/// definition itself, in which case `DEFAULT_LIST_LENGTH` items will be generated, or as a map
+/// containing the item definition under `of` alongside the length options.
+///
+/// # Examples
+///
+/// Between 1 and 3 templates, inclusive:
+///
+/// ```json
+/// {
+///     "type": "List",
+///     "value": {
+///         "of": { "type": "Model", "value": "template" },
+///         "min": 1,
+///         "max": 3
+///     }
+/// }
+/// ```
+///
+/// Exactly 20 apartments:
+///
+/// ```json
+/// {
+///     "type": "List",
+///     "value": {
+///         "of": { "type": "Model", "value": "apartment" },
+///         "count": 20
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Clone)]
+#[serde(untagged)]
+pub enum ListDefinition {
+	Sized {
+		/// The definition of each item in the list
+		of: Box<DataType>,
+		/// A fixed number of items to generate. Takes precedence over `min` and `max`
+		count: Option<usize>,
+		/// The minimum number of items to generate, inclusive. Defaults to 0
+		min: Option<usize>,
+		/// The maximum number of items to generate, inclusive. Defaults to `DEFAULT_LIST_LENGTH`,
+		/// or to `min` where that is larger
+		max: Option<usize>,
+	},
+	Unsized(Box<DataType>),
+}
+
+impl ListDefinition {
+	/// The definition of each item in the list
+	pub fn item_type(&self) -> &DataType {
+		match self {
+			ListDefinition::Sized { of, .. } => of,
+			ListDefinition::Unsized(of) => of,
+		}
+	}
+
+	/// Pick the number of items that should be generated for a single instance of this list
+	pub fn length<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+		match self {
+			ListDefinition::Sized {
+				count: Some(count), ..
+			} => *count,
+			ListDefinition::Sized { min, max, .. } => {
+				let min = min.unwrap_or(0);
+				let max = max.unwrap_or_else(|| min.max(DEFAULT_LIST_LENGTH));
+				if max <= min {
+					min
+				} else {
+					rng.gen_range(min..=max)
+				}
+			}
+			ListDefinition::Unsized(_) => DEFAULT_LIST_LENGTH,
+		}
+	}
+}
+
+/// The text of a `Template`, where each `{name}` is replaced by a value. A name is looked up, in
+/// order, as:
+///
+/// - one of the template's own inline `values`
+/// - another property of the same model, which is generated before the template
+/// - a `^` reference path to the property of a parent, such as `^.id` or `^~^.id`
+/// - `#`, the position of the model in its parent's list
+/// - a `RandomData` type without options, such as `UUID4`
+///
+/// A name can be followed by a width, as in `{seq:6}`, to pad the value with spaces to at least that
+/// many characters, or `{seq:06}` to pad it with zeros. `{{` and `}}` are written as `{` and `}`,
+/// and `null` values are written as nothing. The template can either be written as the text
+/// itself, or as a map containing the text under `template` alongside its `values`.
+///
+/// # Examples
+///
+/// ```json
+/// {
+///     "type": "Template",
+///     "value": {
+///         "template": "INV-{year}-{number:06}",
+///         "values": {
+///             "number": { "NumberBetween": { "min": 1, "max": 999999 } }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Clone)]
+#[serde(untagged)]
+pub enum TemplateDefinition {
+	WithValues {
+		template: String,
+		/// Values that are only used by this template, generated once each time it is
+		#[serde(default)]
+		values: BTreeMap<String, DataType>,
+	},
+	Plain(String),
+}
+
+/// A piece of a parsed `Template`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TemplatePart {
+	Text(String),
+	Field(TemplateField),
+}
+
+/// A `{name}` to be replaced in a `Template`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateField {
+	pub name: String,
+	/// The minimum number of characters to write, padding on the left where the value is shorter
+	pub width: usize,
+	/// Whether padding uses zeros rather than spaces
+	pub zero_fill: bool,
+}
+
+/// Where the value of a `TemplateField` comes from
+pub enum FieldSource<'a> {
+	/// One of the template's inline values
+	Value(&'a DataType),
+	/// Another property of the model the template belongs to
+	Sibling(&'a String),
+	/// A reference or `RandomData` type written in the field name itself
+	Generated(DataType),
+}
+
+impl TemplateDefinition {
+	pub fn template(&self) -> &str {
+		match self {
+			TemplateDefinition::WithValues { template, .. } => template,
+			TemplateDefinition::Plain(template) => template,
+		}
+	}
+
+	pub fn value(&self, name: &str) -> Option<&DataType> {
+		match self {
+			TemplateDefinition::WithValues { values, .. } => values.get(name),
+			TemplateDefinition::Plain(_) => None,
+		}
+	}
+
+	/// Split the template into the text that is kept as it is and the fields that are replaced
+	pub fn parts(&self) -> Result<Vec<TemplatePart>, String> {
+		let template = self.template();
+		let mut parts = Vec::new();
+		let mut text = String::new();
+		let mut chars = template.chars().peekable();
+
+		while let Some(c) = chars.next() {
+			match c {
+				'{' if chars.peek() == Some(&'{') => {
+					chars.next();
+					text.push('{');
+				}
+				'}' if chars.peek() == Some(&'}') => {
+					chars.next();
+					text.push('}');
+				}
+				'{' => {
+					let mut field = String::new();
+					loop {
+						match chars.next() {
+							Some('}') => break,
+							Some('{') | None => {
+								return Err(format!("unclosed `{{` in template `{}`", template))
+							}
+							Some(c) => field.push(c),
+						}
+					}
+					if !text.is_empty() {
+						parts.push(TemplatePart::Text(std::mem::replace(
+							&mut text,
+							String::new(),
+						)));
+					}
+					parts.push(TemplatePart::Field(TemplateField::parse(&field)?));
+				}
+				'}' => return Err(format!("unmatched `}}` in template `{}`", template)),
+				c => text.push(c),
+			}
+		}
+		if !text.is_empty() {
+			parts.push(TemplatePart::Text(text));
+		}
+		Ok(parts)
+	}
+
+	/// Find where the value of the named field comes from, for a template in the given model
+	pub fn source<'a>(&'a self, name: &str, model: &'a Model) -> Result<FieldSource<'a>, String> {
+		if let Some(value) = self.value(name) {
+			return Ok(FieldSource::Value(value));
+		}
+		if let Some(property) = model.properties.keys().find(|property| *property == name) {
+			return Ok(FieldSource::Sibling(property));
+		}
+		match data_type_from_str::<serde::de::value::Error>(name) {
+			Ok(data_type @ DataType::Reference { .. })
+			| Ok(data_type @ DataType::RandomData(_))
+			| Ok(data_type @ DataType::Sequence { .. }) => Ok(FieldSource::Generated(data_type)),
+			_ => Err(format!(
+				"template field `{}` is not a value, a property, a `^` reference, `#` or a RandomData type",
+				name
+			)),
+		}
+	}
+}
+
+impl TemplateField {
+	fn parse(field: &str) -> Result<Self, String> {
+		let mut pieces = field.splitn(2, ':');
+		let name = pieces.next().unwrap_or_default().trim();
+		if name.is_empty() {
+			return Err(format!("template field `{{{}}}` must have a name", field));
+		}
+		let (width, zero_fill) = match pieces.next() {
+			Some(format) => match format.parse::<usize>() {
+				Ok(width) => (width, format.starts_with('0')),
+				Err(_) => {
+					return Err(format!(
+						"invalid width `{}` for template field `{}`, expected a number such as `6` or `06`",
+						format, name
+					))
+				}
+			},
+			None => (0, false),
+		};
+		Ok(TemplateField {
+			name: name.to_string(),
+			width,
+			zero_fill,
+		})
+	}
+
+	/// Pad the text of a value out to the width of this field
+	pub fn pad(&self, text: &str) -> String {
+		let length = text.chars().count();
+		if length >= self.width {
+			return text.to_string();
+		}
+		let padding = self.width - length;
+		if !self.zero_fill {
+			format!("{}{}", " ".repeat(padding), text)
+		} else if text.starts_with('-') {
+			format!("-{}{}", "0".repeat(padding), &text[1..])
+		} else {
+			format!("{}{}", "0".repeat(padding), text)
+		}
+	}
+}
+
+use std::collections::{btree_map::Iter, BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct Model {
 	#[serde(flatten)]
-	properties: HashMap<String, DataType>,
+	properties: BTreeMap<String, DataType>,
+	/// The property names in the order they were written in the specification
+	#[serde(skip)]
+	declaration_order: Vec<String>,
+	/// The models whose properties this model inherits, written under an `extends` key. These are
+	/// copied into the model by `Specification::resolve_extends`, after which the list is left empty
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	extends: Vec<String>,
+	/// The sets of properties that must be unique across instances of this model, written under a
+	/// `unique` key. Models also inherit the unique keys of the models they extend
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	unique: Vec<UniqueKey>,
 }
 
 impl Model {
+	/// Iterate over the property definitions of this model. Properties are always visited in the
+	/// same order, so that generation with a seed is repeatable
 	pub fn type_iter(&self) -> Iter<String, DataType> {
 		self.properties.iter()
 	}
+	/// The names of this model's properties, in the order they were written in the specification
+	pub fn declaration_order(&self) -> &Vec<String> {
+		&self.declaration_order
+	}
+	/// Every set of properties that must be unique across instances of this model, including each
+	/// property that is itself `Unique`
+	pub fn unique_keys(&self) -> Vec<UniqueKey> {
+		let mut keys: Vec<UniqueKey> = self
+			.declaration_order
+			.iter()
+			.filter_map(|property| match &self.properties[property] {
+				DataType::Unique { scope, .. } => Some(UniqueKey::Scoped {
+					properties: vec![property.clone()],
+					scope: *scope,
+				}),
+				_ => None,
+			})
+			.collect();
+		keys.extend(self.unique.iter().cloned());
+		keys
+	}
+	/// The other properties of this model that a `Template` uses, which must be generated before
+	/// it. Data types that are not templates have none
+	pub fn template_dependencies(&self, data_type: &DataType) -> Vec<String> {
+		let template = match data_type.element_type() {
+			DataType::Template(template) => template,
+			_ => return Vec::new(),
+		};
+		let mut dependencies = Vec::new();
+		for part in template.parts().unwrap_or_default() {
+			let field = match part {
+				TemplatePart::Field(field) => field,
+				TemplatePart::Text(_) => continue,
+			};
+			match template.source(&field.name, self) {
+				Ok(FieldSource::Sibling(property)) => dependencies.push(property.clone()),
+				Ok(FieldSource::Value(value)) => {
+					dependencies.extend(self.template_dependencies(value))
+				}
+				_ => {}
+			}
+		}
+		dependencies
+	}
+}
+
+const DATA_TYPE_VARIANTS: &[&str] = &[
+	"RandomData",
+	"List",
+	"Model",
+	"Reference",
+	"ForeignKey",
+	"Nullable",
+	"Template",
+	"Sequence",
+	"Unique",
+	"Localized",
+];
+
+#[derive(Deserialize)]
+struct NullableValue {
+	of: Box<DataType>,
+	probability: f64,
+}
+
+#[derive(Deserialize)]
+struct LocalizedValue {
+	of: Box<DataType>,
+	locale: Locale,
+}
+
+#[derive(Deserialize)]
+struct UniqueValue {
+	of: Box<DataType>,
+	scope: Option<UniqueScope>,
+}
+
+#[derive(Deserialize)]
+struct SequenceValue {
+	start: Option<i64>,
+	step: Option<i64>,
+	scope: Option<SequenceScope>,
+}
+
+#[derive(Deserialize)]
+struct ForeignKeyValue {
+	model: String,
+	property: String,
+	selection: Option<Selection>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BoundedRelation {
+	model: String,
+	max_depth: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SizedList {
+	of: Box<DataType>,
+	count: Option<usize>,
+	min: Option<usize>,
+	max: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateWithValues {
+	template: String,
+	#[serde(default)]
+	values: BTreeMap<String, DataType>,
+}
+
+// The definitions below can be written in full or as a shorthand. Rather than trying each form in
+// turn, as an untagged enum would, the form is chosen by whether the full form's main key is there,
+// so that a mistake in it is reported instead of a failure to match either form
+
+impl<'de> serde::Deserialize<'de> for ModelRelation {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		match serde_json::Value::deserialize(deserializer)? {
+			serde_json::Value::String(model) => Ok(ModelRelation::Named(model)),
+			value => BoundedRelation::deserialize(value)
+				.map(|relation| ModelRelation::Bounded {
+					model: relation.model,
+					max_depth: relation.max_depth,
+				})
+				.map_err(D::Error::custom),
+		}
+	}
+}
+
+impl<'de> serde::Deserialize<'de> for ListDefinition {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let value = serde_json::Value::deserialize(deserializer)?;
+		if value.get("of").is_some() {
+			SizedList::deserialize(value)
+				.map(|list| ListDefinition::Sized {
+					of: list.of,
+					count: list.count,
+					min: list.min,
+					max: list.max,
+				})
+				.map_err(D::Error::custom)
+		} else {
+			DataType::deserialize(value)
+				.map(|of| ListDefinition::Unsized(Box::new(of)))
+				.map_err(D::Error::custom)
+		}
+	}
+}
+
+impl<'de> serde::Deserialize<'de> for TemplateDefinition {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		match serde_json::Value::deserialize(deserializer)? {
+			serde_json::Value::String(template) => Ok(TemplateDefinition::Plain(template)),
+			value => TemplateWithValues::deserialize(value)
+				.map(|template| TemplateDefinition::WithValues {
+					template: template.template,
+					values: template.values,
+				})
+				.map_err(D::Error::custom),
+		}
+	}
+}
+
+/// Reads the `value` of a fully written out `DataType`, once its `type` is known
+struct TaggedValue<'a>(&'a str);
+
+impl<'de, 'a> DeserializeSeed<'de> for TaggedValue<'a> {
+	type Value = DataType;
+
+	fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<DataType, D::Error> {
+		use serde::Deserialize;
+		match self.0 {
+			"RandomData" => RandomData::deserialize(deserializer).map(DataType::RandomData),
+			"List" => ListDefinition::deserialize(deserializer).map(DataType::List),
+			"Model" => ModelRelation::deserialize(deserializer).map(DataType::Model),
+			"Reference" => {
+				Reference::deserialize(deserializer).map(|reference| DataType::Reference {
+					path: reference.path,
+					property: reference.property,
+				})
+			}
+			"ForeignKey" => {
+				ForeignKeyValue::deserialize(deserializer).map(|foreign_key| DataType::ForeignKey {
+					model: foreign_key.model,
+					property: foreign_key.property,
+					selection: foreign_key.selection,
+				})
+			}
+			"Nullable" => {
+				NullableValue::deserialize(deserializer).map(|nullable| DataType::Nullable {
+					of: nullable.of,
+					probability: nullable.probability,
+				})
+			}
+			"Template" => TemplateDefinition::deserialize(deserializer).map(DataType::Template),
+			"Unique" => UniqueValue::deserialize(deserializer).map(|unique| DataType::Unique {
+				of: unique.of,
+				scope: unique.scope,
+			}),
+			"Localized" => {
+				LocalizedValue::deserialize(deserializer).map(|localized| DataType::Localized {
+					of: localized.of,
+					locale: localized.locale,
+				})
+			}
+			"Sequence" => {
+				SequenceValue::deserialize(deserializer).map(|sequence| DataType::Sequence {
+					start: sequence.start,
+					step: sequence.step,
+					scope: sequence.scope,
+				})
+			}
+			other => Err(D::Error::unknown_variant(other, DATA_TYPE_VARIANTS)),
+		}
+	}
+}
+
+/// Read the string shorthand for a `DataType`
+fn data_type_from_str<E: DeError>(value: &str) -> Result<DataType, E> {
+	if value == "#" {
+		Ok(DataType::Sequence {
+			start: Some(0),
+			step: None,
+			scope: Some(SequenceScope::Parent),
+		})
+	} else if value.starts_with('@') {
+		Ok(DataType::Model(ModelRelation::Named(
+			value[1..].to_string(),
+		)))
+	} else if value.starts_with('^') {
+		let mut parts = value.splitn(2, '.');
+		let path = parts.next().unwrap_or_default();
+		match parts.next() {
+			Some(property) if !property.is_empty() => Ok(DataType::Reference {
+				path: path.to_string(),
+				property: property.to_string(),
+			}),
+			_ => Err(E::custom(format!(
+				"reference `{}` must end with the property to copy, as in `^.id`",
+				value
+			))),
+		}
+	} else if value.contains('{') {
+		Ok(DataType::Template(TemplateDefinition::Plain(
+			value.to_string(),
+		)))
+	} else {
+		use serde::Deserialize;
+		RandomData::deserialize(value.into_deserializer()).map(DataType::RandomData)
+	}
+}
+
+struct DataTypeVisitor;
+
+impl<'de> Visitor<'de> for DataTypeVisitor {
+	type Value = DataType;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a data type definition")
+	}
+
+	fn visit_str<E: DeError>(self, value: &str) -> Result<DataType, E> {
+		data_type_from_str(value)
+	}
+
+	fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DataType, A::Error> {
+		let expected = "a list containing a single item definition";
+		let item = match seq.next_element::<DataType>()? {
+			Some(item) => item,
+			None => return Err(A::Error::invalid_length(0, &expected)),
+		};
+		if seq.next_element::<DataType>()?.is_some() {
+			return Err(A::Error::invalid_length(2, &expected));
+		}
+		Ok(DataType::List(ListDefinition::Unsized(Box::new(item))))
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<DataType, A::Error> {
+		const FIELDS: &[&str] = &["type", "value", "nullable", "unique", "locale"];
+		let mut tag: Option<String> = None;
+		let mut data_type: Option<DataType> = None;
+		// The value can only be read once the type is known, so it is held until then
+		let mut held_value: Option<serde_json::Value> = None;
+		let mut nullable: Option<f64> = None;
+		let mut unique: Option<UniqueFlag> = None;
+		let mut locale: Option<Locale> = None;
+
+		while let Some(key) = map.next_key::<String>()? {
+			let defined = tag.is_some() || data_type.is_some() || held_value.is_some();
+			match key.as_str() {
+				"type" if tag.is_none() => tag = Some(map.next_value::<String>()?),
+				"value" if data_type.is_none() && held_value.is_none() => match &tag {
+					Some(tag) => data_type = Some(map.next_value_seed(TaggedValue(tag))?),
+					None => held_value = Some(map.next_value::<serde_json::Value>()?),
+				},
+				"nullable" if nullable.is_none() => nullable = Some(map.next_value::<f64>()?),
+				"unique" if unique.is_none() => unique = Some(map.next_value::<UniqueFlag>()?),
+				"locale" if locale.is_none() => locale = Some(map.next_value::<Locale>()?),
+				"type" | "value" | "nullable" | "unique" | "locale" => {
+					return Err(A::Error::custom(format!("duplicate field `{}`", key)))
+				}
+				// Any other key is the name of a `RandomData` type that takes options
+				_ if !defined => {
+					use serde::Deserialize;
+					let options = map.next_value::<serde_json::Value>()?;
+					let mut random_data = serde_json::Map::new();
+					random_data.insert(key, options);
+					data_type = Some(
+						RandomData::deserialize(serde_json::Value::Object(random_data))
+							.map(DataType::RandomData)
+							.map_err(A::Error::custom)?,
+					);
+				}
+				_ => return Err(A::Error::unknown_field(&key, FIELDS)),
+			}
+		}
+
+		let data_type = match (data_type, held_value, tag) {
+			(Some(data_type), _, _) => data_type,
+			(None, Some(value), Some(tag)) => TaggedValue(&tag)
+				.deserialize(value)
+				.map_err(A::Error::custom)?,
+			(None, None, Some(_)) => return Err(A::Error::missing_field("value")),
+			(None, _, None) => return Err(A::Error::missing_field("type")),
+		};
+		let data_type = match locale {
+			Some(locale) => DataType::Localized {
+				of: Box::new(data_type),
+				locale,
+			},
+			None => data_type,
+		};
+		let data_type = match nullable {
+			Some(probability) => DataType::Nullable {
+				of: Box::new(data_type),
+				probability,
+			},
+			None => data_type,
+		};
+		Ok(match unique {
+			Some(UniqueFlag::Enabled(true)) => DataType::Unique {
+				of: Box::new(data_type),
+				scope: None,
+			},
+			Some(UniqueFlag::Scope(scope)) => DataType::Unique {
+				of: Box::new(data_type),
+				scope: Some(scope),
+			},
+			Some(UniqueFlag::Enabled(false)) | None => data_type,
+		})
+	}
+}
+
+impl<'de> serde::Deserialize<'de> for DataType {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_any(DataTypeVisitor)
+	}
+}
+
+struct ModelVisitor;
+
+impl<'de> Visitor<'de> for ModelVisitor {
+	type Value = Model;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a map of property names to data types")
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Model, A::Error> {
+		let mut model = Model::default();
+		while let Some(name) = map.next_key::<String>()? {
+			let data_type = if name == "extends" || name == "unique" {
+				// These names are only model settings when given a list, and are properties otherwise
+				use serde::Deserialize;
+				let value = map.next_value::<serde_json::Value>()?;
+				match (name.as_str(), value.is_array()) {
+					("extends", true) => {
+						model.extends = Vec::<String>::deserialize(value).map_err(|e| {
+							with_context(String::from("`extends`"), A::Error::custom(e))
+						})?;
+						continue;
+					}
+					("unique", true) => {
+						model.unique = Vec::<UniqueKey>::deserialize(value).map_err(|e| {
+							with_context(String::from("`unique`"), A::Error::custom(e))
+						})?;
+						continue;
+					}
+					_ => DataType::deserialize(value).map_err(|e| {
+						with_context(format!("property `{}`", name), A::Error::custom(e))
+					})?,
+				}
+			} else {
+				map.next_value::<DataType>()
+					.map_err(|e| with_context(format!("property `{}`", name), e))?
+			};
+			if model.properties.insert(name.clone(), data_type).is_none() {
+				model.declaration_order.push(name);
+			}
+		}
+		Ok(model)
+	}
+}
+
+lazy_static! {
+	static ref POSITION: Regex = Regex::new(r" at line \d+ column \d+").unwrap();
+}
+
+/// Deserialisation errors gain another position each time they are passed back up through a map,
+/// but only the first position points at the actual problem. This keeps the first position and
+/// moves it to the end of the message
+fn first_position_only(message: &str) -> String {
+	match POSITION.find(message) {
+		Some(position) => format!("{}{}", POSITION.replace_all(message, ""), position.as_str()),
+		None => message.to_string(),
+	}
+}
+
+/// Add the name of the model or property being read to a deserialisation error
+fn with_context<E: DeError>(context: String, error: E) -> E {
+	E::custom(format!(
+		"{}: {}",
+		context,
+		first_position_only(&error.to_string())
+	))
+}
+
+struct ModelMapVisitor;
+
+impl<'de> Visitor<'de> for ModelMapVisitor {
+	type Value = HashMap<String, Model>;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str("a map of model names to model definitions")
+	}
+
+	fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+		let mut models = HashMap::new();
+		while let Some(name) = map.next_key::<String>()? {
+			let model = map
+				.next_value::<Model>()
+				.map_err(|e| with_context(format!("model `{}`", name), e))?;
+			models.insert(name, model);
+		}
+		Ok(models)
+	}
+}
+
+fn deserialize_models<'de, D: Deserializer<'de>>(
+	deserializer: D,
+) -> Result<HashMap<String, Model>, D::Error> {
+	deserializer.deserialize_map(ModelMapVisitor)
+}
+
+impl<'de> serde::Deserialize<'de> for Model {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		deserializer.deserialize_map(ModelVisitor)
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Specification {
+	/// Other spec files to add models and `serialize` entries from. These are only read by
+	/// `io::read_spec`, after which the list is left empty
+	#[serde(default, alias = "import", skip_serializing_if = "Vec::is_empty")]
+	include: Vec<Include>,
+	#[serde(default)]
 	serialize: HashMap<String, Vec<String>>,
+	/// The locale that names, addresses and other text are generated for, unless a property sets
+	/// its own. Defaults to `en`
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	locale: Option<Locale>,
+	#[serde(deserialize_with = "deserialize_models")]
 	models: HashMap<String, Model>,
+	/// The canonical path of the spec file that each model was read from, so that a file included
+	/// more than once into the same namespace only adds its models once. Only set by `io::read_spec`
+	#[serde(skip)]
+	sources: HashMap<String, PathBuf>,
+}
+
+/// Add each of the given names that is not already in the list to the end of it
+fn extend_unique(list: &mut Vec<String>, names: &[String]) {
+	for name in names {
+		if !list.contains(name) {
+			list.push(name.clone());
+		}
+	}
+}
+
+/// The separator between a namespace and a model name, as in `common::user`
+pub const NAMESPACE_SEPARATOR: &str = "::";
+
+/// Another spec file to add models from, with its path given relative to the file that includes
+/// it. When `as` is given, each included model is renamed into that namespace, so the `user`
+/// model of a spec included as `common` becomes `common::user`.
+///
+/// # Examples
+///
+/// ```json
+/// {
+///     "include": [
+///         { "path": "shared/common.json", "as": "common" },
+///         { "path": "audit.yaml" }
+///     ],
+///     "models": {
+///         "post": { "author": "@common::user" }
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Include {
+	pub path: String,
+	#[serde(rename = "as")]
+	pub namespace: Option<String>,
+}
+
+/// A problem found in a `Specification` that would stop data from being generated correctly
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+	/// Where the problem is, written as a dotted path such as `models.user.age`
+	pub location: String,
+	pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}: {}", self.location, self.message)
+	}
+}
+
+/// A set of root models to generate in a single run, alongside the number of instances of each.
+/// Written in a file as a map of model names to quantities under a `models` key
+///
+/// # Examples
+///
+/// ```json
+/// {
+///     "models": {
+///         "user": 200,
+///         "post": 50
+///     }
+/// }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RunPlan {
+	models: BTreeMap<String, usize>,
+}
+
+impl RunPlan {
+	pub fn new() -> Self {
+		RunPlan::default()
+	}
+	/// Add a root model to the plan, replacing the quantity of that model if it is already present
+	pub fn add_model<S: ToString>(&mut self, name: S, quantity: usize) -> &mut Self {
+		self.models.insert(name.to_string(), quantity);
+		self
+	}
+	pub fn model_iter(&self) -> Iter<String, usize> {
+		self.models.iter()
+	}
+}
+
+impl DataType {
+	/// The data type that is generated when this one is not `null`, looking through any `Nullable`,
+	/// `Unique` or `Localized`
+	pub fn non_null(&self) -> &DataType {
+		match self {
+			DataType::Nullable { of, .. }
+			| DataType::Unique { of, .. }
+			| DataType::Localized { of, .. } => of.non_null(),
+			other => other,
+		}
+	}
+
+	/// The data type of each value generated for a property: the item type of a `List`, or the
+	/// data type itself otherwise. Any `Nullable`, `Unique` or `Localized` is looked through
+	pub fn element_type(&self) -> &DataType {
+		match self.non_null() {
+			DataType::List(nested) => nested.item_type().non_null(),
+			other => other,
+		}
+	}
+
+	/// Rename each model that this data type points at, where `rename` gives a new name for it
+	fn rename_models<F: Fn(&str) -> Option<String>>(&mut self, rename: &F) {
+		match self {
+			DataType::Model(ModelRelation::Named(model))
+			| DataType::Model(ModelRelation::Bounded { model, .. })
+			| DataType::ForeignKey { model, .. } => {
+				if let Some(name) = rename(model) {
+					*model = name;
+				}
+			}
+			DataType::List(ListDefinition::Sized { of, .. })
+			| DataType::List(ListDefinition::Unsized(of))
+			| DataType::Nullable { of, .. }
+			| DataType::Unique { of, .. }
+			| DataType::Localized { of, .. } => of.rename_models(rename),
+			DataType::Template(TemplateDefinition::WithValues { values, .. }) => values
+				.values_mut()
+				.for_each(|value| value.rename_models(rename)),
+			DataType::RandomData(_)
+			| DataType::Reference { .. }
+			| DataType::Sequence { .. }
+			| DataType::Template(TemplateDefinition::Plain(_)) => {}
+		}
+	}
+}
+
+/// Follow the templates that use the last property in `path`, returning whether they lead back to
+/// the first. Where they do, `path` is left holding the chain of properties that does so
+fn template_cycle(model: &Model, path: &mut Vec<String>) -> bool {
+	let last = match path.last() {
+		Some(last) => last.clone(),
+		None => return false,
+	};
+	for dependency in model.template_dependencies(&model.properties[&last]) {
+		if dependency == path[0] {
+			path.push(dependency);
+			return true;
+		}
+		if path.contains(&dependency) {
+			continue;
+		}
+		path.push(dependency);
+		if template_cycle(model, path) {
+			return true;
+		}
+		path.pop();
+	}
+	false
 }
 
 impl Specification {
+	/// Take the list of spec files that should be included into this one
+	pub(crate) fn take_includes(&mut self) -> Vec<Include> {
+		std::mem::replace(&mut self.include, Vec::new())
+	}
+
+	/// Mark every model in this spec as read from the given spec file
+	pub(crate) fn set_source(&mut self, path: &Path) {
+		self.sources = self
+			.models
+			.keys()
+			.map(|name| (name.clone(), path.to_path_buf()))
+			.collect();
+	}
+
+	/// Add the models and `serialize` entries of another spec to this one. With a namespace, each
+	/// added model is renamed to `namespace::model`, along with every reference to it from the
+	/// other spec. Models that were read from the same spec file into the same namespace as one
+	/// that is already defined are left out, as the file has been included before. Otherwise,
+	/// nothing is added if any of the models or `serialize` entries is already defined, or if the
+	/// other spec sets a different locale
+	pub fn merge(&mut self, other: Specification, namespace: Option<&str>) -> Result<(), String> {
+		if let Some(locale) = other.locale {
+			if locale != self.locale() {
+				return Err(format!(
+					"its locale `{}` is not the `{}` locale of the spec that includes it, set the locale on its properties instead",
+					locale,
+					self.locale()
+				));
+			}
+		}
+
+		let rename = |name: &str| -> Option<String> {
+			match namespace {
+				Some(namespace) if other.models.contains_key(name) => {
+					Some(format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, name))
+				}
+				_ => None,
+			}
+		};
+		let full_name = |name: &String| rename(name).unwrap_or_else(|| name.clone());
+
+		let included_before: HashSet<&String> = other
+			.models
+			.keys()
+			.filter(|name| {
+				other.sources.get(*name).map_or(false, |source| {
+					self.sources.get(&full_name(name)) == Some(source)
+				})
+			})
+			.collect();
+		let mut conflicts: Vec<String> = other
+			.models
+			.keys()
+			.filter(|name| !included_before.contains(name))
+			.map(&full_name)
+			.filter(|name| self.models.contains_key(name))
+			.map(|name| format!("model `{}` is already defined", name))
+			.chain(
+				other
+					.serialize
+					.keys()
+					.filter(|name| !included_before.contains(name))
+					.map(&full_name)
+					.filter(|name| self.serialize.contains_key(name))
+					.map(|name| format!("`serialize` entry `{}` is already defined", name)),
+			)
+			.collect();
+		if !conflicts.is_empty() {
+			conflicts.sort();
+			return Err(conflicts.join(", "));
+		}
+
+		let mut models = HashMap::new();
+		for (name, model) in &other.models {
+			if included_before.contains(name) {
+				continue;
+			}
+			let mut model = model.clone();
+			for data_type in model.properties.values_mut() {
+				data_type.rename_models(&rename);
+			}
+			models.insert(full_name(name), model);
+		}
+		let serialize: Vec<(String, Vec<String>)> = other
+			.serialize
+			.iter()
+			.filter(|(name, _)| !included_before.contains(name))
+			.map(|(name, order)| (full_name(name), order.clone()))
+			.collect();
+		let sources: Vec<(String, PathBuf)> = other
+			.sources
+			.iter()
+			.filter(|(name, _)| !included_before.contains(name))
+			.map(|(name, source)| (full_name(name), source.clone()))
+			.collect();
+
+		self.models.extend(models);
+		self.serialize.extend(serialize);
+		self.sources.extend(sources);
+		Ok(())
+	}
+
+	/// Copy the properties of the models that each model `extends` into it. Parents are applied in
+	/// the order they are listed, with later parents overriding the properties of earlier ones, and
+	/// the model's own properties overriding all of them. A model that extends a model with a
+	/// `serialize` entry gets one too, made of its parents' entries followed by its own
+	pub fn resolve_extends(&mut self) -> Result<(), String> {
+		let mut resolved = Vec::new();
+		let mut model_names: Vec<String> = self.models.keys().cloned().collect();
+		model_names.sort();
+		for model_name in model_names {
+			self.resolve_model(&model_name, &mut Vec::new(), &mut resolved)?;
+		}
+		Ok(())
+	}
+
+	fn resolve_model(
+		&mut self,
+		model_name: &str,
+		path: &mut Vec<String>,
+		resolved: &mut Vec<String>,
+	) -> Result<(), String> {
+		if resolved.iter().any(|name| name == model_name) {
+			return Ok(());
+		}
+		if let Some(start) = path.iter().position(|name| name == model_name) {
+			return Err(format!(
+				"model `{}` extends itself through `{} -> {}`",
+				model_name,
+				path[start..].join(" -> "),
+				model_name
+			));
+		}
+		let parents = match self.models.get(model_name) {
+			Some(model) => model.extends.clone(),
+			None => return Ok(()),
+		};
+
+		let mut properties = BTreeMap::new();
+		let mut declaration_order: Vec<String> = Vec::new();
+		let mut serialize: Option<Vec<String>> = None;
+		let mut unique: Vec<UniqueKey> = Vec::new();
+
+		path.push(model_name.to_string());
+		for parent_name in &parents {
+			if !self.has_model(parent_name) {
+				return Err(format!(
+					"model `{}` extends unknown model `{}`",
+					model_name, parent_name
+				));
+			}
+			self.resolve_model(parent_name, path, resolved)?;
+
+			let parent = &self.models[parent_name];
+			for property in parent.declaration_order() {
+				if !properties.contains_key(property) {
+					declaration_order.push(property.clone());
+				}
+				properties.insert(property.clone(), parent.properties[property].clone());
+			}
+			if let Some(order) = self.serialize.get(parent_name) {
+				extend_unique(serialize.get_or_insert_with(Vec::new), order);
+			}
+			for key in &parent.unique {
+				if !unique.contains(key) {
+					unique.push(key.clone());
+				}
+			}
+		}
+		path.pop();
+
+		let model = self.models.get_mut(model_name).unwrap();
+		for property in &model.declaration_order {
+			if !properties.contains_key(property) {
+				declaration_order.push(property.clone());
+			}
+		}
+		properties.extend(std::mem::replace(&mut model.properties, BTreeMap::new()));
+		model.properties = properties;
+		model.declaration_order = declaration_order;
+		model.extends.clear();
+		for key in std::mem::replace(&mut model.unique, Vec::new()) {
+			if !unique.contains(&key) {
+				unique.push(key);
+			}
+		}
+		model.unique = unique;
+
+		// Without an entry of its own, the model still writes its other properties, after the
+		// ones its parents' entries list
+		let own_order = match self.serialize.get(model_name) {
+			Some(order) => order.clone(),
+			None => self.column_order(model_name),
+		};
+		if let Some(mut order) = serialize {
+			extend_unique(&mut order, &own_order);
+			self.serialize.insert(model_name.to_string(), order);
+		}
+
+		resolved.push(model_name.to_string());
+		Ok(())
+	}
+
 	pub fn has_model<S: ToString>(&self, name: S) -> bool {
 		self.models.contains_key(&name.to_string())
 	}
-	pub fn get_definition<S: ToString>(&self, name: S) -> &Model {
-		&self.models.get(&name.to_string()).unwrap()
+	pub fn get_definition<S: ToString>(&self, name: S) -> Option<&Model> {
+		self.models.get(&name.to_string())
+	}
+	/// The locale used by properties that do not set their own
+	pub fn locale(&self) -> Locale {
+		self.locale.unwrap_or_default()
 	}
 	pub fn get_serialize_ref<S: ToString>(&self, name: S) -> Option<&Vec<String>> {
 		self.serialize.get(&name.to_string())
 	}
+	/// Check every model and `serialize` entry for problems that would stop data from being
+	/// generated correctly, returning all of them at once
+	pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+		let mut errors = Vec::new();
+		let locale = self.locale();
+
+		for model_name in self.sorted_model_names() {
+			let model = &self.models[model_name];
+			if !model.extends.is_empty() {
+				errors.push(ValidationError {
+					location: format!("models.{}.extends", model_name),
+					message: String::from(
+						"extends must be resolved with `resolve_extends` before the spec is used",
+					),
+				});
+			}
+			for property in model.declaration_order() {
+				let location = format!("models.{}.{}", model_name, property);
+				let data_type = match &model.properties[property] {
+					DataType::Unique { of, .. } => of,
+					other => other,
+				};
+				self.validate_data_type(model_name, data_type, locale, &location, &mut errors);
+			}
+
+			let location = format!("models.{}.unique", model_name);
+			for key in model.unique_keys() {
+				if key.properties().is_empty() {
+					errors.push(ValidationError {
+						location: location.clone(),
+						message: String::from("unique keys must name at least 1 property"),
+					});
+				}
+				for property in key.properties() {
+					let message = match model.properties.get(property).map(DataType::element_type) {
+						Some(DataType::Model(_)) => format!(
+							"property `{}` is a child model, which can not be unique",
+							property
+						),
+						Some(_) => continue,
+						None => format!("model `{}` has no property `{}`", model_name, property),
+					};
+					errors.push(ValidationError {
+						location: location.clone(),
+						message,
+					});
+				}
+			}
+		}
+
+		let mut finished = Vec::new();
+		for model_name in self.sorted_model_names() {
+			self.find_cycles(model_name, &mut Vec::new(), &mut finished, &mut errors);
+		}
+
+		for model_name in self.sorted_model_names() {
+			let model = &self.models[model_name];
+			for property in model.declaration_order() {
+				let mut path = vec![property.clone()];
+				if template_cycle(model, &mut path) {
+					errors.push(ValidationError {
+						location: format!("models.{}.{}", model_name, property),
+						message: format!(
+							"template uses its own value through `{}`",
+							path.join(" -> ")
+						),
+					});
+				}
+			}
+		}
+
+		let mut serialized: Vec<(&String, &Vec<String>)> = self.serialize.iter().collect();
+		serialized.sort();
+		for (model_name, properties) in serialized {
+			let location = format!("serialize.{}", model_name);
+			match self.models.get(model_name) {
+				Some(model) => {
+					for property in properties {
+						if !model.properties.contains_key(property) {
+							errors.push(ValidationError {
+								location: location.clone(),
+								message: format!(
+									"model `{}` has no property `{}`",
+									model_name, property
+								),
+							});
+						}
+					}
+				}
+				None => errors.push(ValidationError {
+					location,
+					message: format!("unknown model `{}`", model_name),
+				}),
+			}
+		}
+
+		if errors.is_empty() {
+			Ok(())
+		} else {
+			Err(errors)
+		}
+	}
+
+	fn sorted_model_names(&self) -> Vec<&String> {
+		let mut model_names: Vec<&String> = self.models.keys().collect();
+		model_names.sort();
+		model_names
+	}
+
+	/// Follow every `Model` and `List` property without a `max_depth` down from the given model,
+	/// reporting each one that leads back to a model on the current path, as generating it would
+	/// never finish. Models in `finished` have already been searched and are skipped
+	fn find_cycles(
+		&self,
+		model_name: &str,
+		path: &mut Vec<(String, String)>,
+		finished: &mut Vec<String>,
+		errors: &mut Vec<ValidationError>,
+	) {
+		if finished.iter().any(|name| name == model_name) {
+			return;
+		}
+		let model = match self.models.get(model_name) {
+			Some(model) => model,
+			None => return,
+		};
+
+		for property in model.declaration_order() {
+			let child = match model.properties[property].element_type() {
+				DataType::Model(relation) if relation.max_depth().is_none() => relation.name(),
+				_ => continue,
+			};
+
+			path.push((model_name.to_string(), property.clone()));
+			match path.iter().position(|(name, _)| name == child) {
+				Some(start) => {
+					let cycle: Vec<String> = path[start..]
+						.iter()
+						.map(|(name, property)| format!("{}.{}", name, property))
+						.collect();
+					errors.push(ValidationError {
+						location: format!("models.{}.{}", model_name, property),
+						message: format!(
+							"model `{}` contains itself through `{}`, which needs a max_depth",
+							child,
+							cycle.join(" -> ")
+						),
+					});
+				}
+				None => self.find_cycles(child, path, finished, errors),
+			}
+			path.pop();
+		}
+
+		finished.push(model_name.to_string());
+	}
+
+	/// Check each field of a template in the given model
+	fn validate_template(
+		&self,
+		model_name: &str,
+		template: &TemplateDefinition,
+		locale: Locale,
+		location: &str,
+		errors: &mut Vec<ValidationError>,
+	) {
+		let model = match self.models.get(model_name) {
+			Some(model) => model,
+			None => return,
+		};
+		let parts = match template.parts() {
+			Ok(parts) => parts,
+			Err(message) => {
+				errors.push(ValidationError {
+					location: location.to_string(),
+					message,
+				});
+				return;
+			}
+		};
+
+		for part in parts {
+			let field = match part {
+				TemplatePart::Field(field) => field,
+				TemplatePart::Text(_) => continue,
+			};
+			let message = match template.source(&field.name, model) {
+				Ok(FieldSource::Value(value)) => match value.element_type() {
+					DataType::Model(_) => format!(
+						"template value `{}` can not be a model, as it must be written as text",
+						field.name
+					),
+					_ => {
+						self.validate_data_type(model_name, value, locale, location, errors);
+						continue;
+					}
+				},
+				Ok(FieldSource::Sibling(property)) => {
+					match model.properties[property].element_type() {
+						DataType::Model(_) => format!(
+							"template field `{}` is a child model, which can not be written as text",
+							property
+						),
+						_ => continue,
+					}
+				}
+				Ok(FieldSource::Generated(data_type)) => {
+					self.validate_data_type(model_name, &data_type, locale, location, errors);
+					continue;
+				}
+				Err(message) => message,
+			};
+			errors.push(ValidationError {
+				location: location.to_string(),
+				message,
+			});
+		}
+	}
+
+	fn validate_data_type(
+		&self,
+		model_name: &str,
+		data_type: &DataType,
+		locale: Locale,
+		location: &str,
+		errors: &mut Vec<ValidationError>,
+	) {
+		let mut error = |message: String| {
+			errors.push(ValidationError {
+				location: location.to_string(),
+				message,
+			})
+		};
+
+		match data_type {
+			DataType::RandomData(random_data) => {
+				if let Err(message) = random_data
+					.validate()
+					.and_then(|_| random_data.check_locale(locale))
+				{
+					error(message);
+				}
+			}
+			DataType::Localized { of, locale } => {
+				if let DataType::Model(_) = of.element_type() {
+					error(String::from(
+						"locale can not be set on a child model, set it on the child model's properties instead",
+					));
+				}
+				self.validate_data_type(model_name, of, *locale, location, errors)
+			}
+			DataType::Model(relation) => {
+				if !self.has_model(relation.name()) {
+					error(format!("unknown model `{}`", relation.name()));
+				}
+			}
+			DataType::List(nested) => {
+				if let ListDefinition::Sized {
+					min: Some(min),
+					max: Some(max),
+					count: None,
+					..
+				} = nested
+				{
+					if min > max {
+						error(format!(
+							"list min ({}) must not be greater than max ({})",
+							min, max
+						));
+					}
+				}
+				self.validate_data_type(model_name, nested.item_type(), locale, location, errors);
+			}
+			DataType::Nullable { of, probability } => {
+				if *probability < 0.0 || *probability > 1.0 {
+					error(format!(
+						"nullable probability ({}) must be between 0 and 1",
+						probability
+					));
+				}
+				self.validate_data_type(model_name, of, locale, location, errors);
+			}
+			DataType::Template(template) => {
+				self.validate_template(model_name, template, locale, location, errors)
+			}
+			DataType::Unique { of, .. } => {
+				error(String::from(
+					"unique can only be set on a whole property, not on part of one",
+				));
+				self.validate_data_type(model_name, of, locale, location, errors);
+			}
+			DataType::Sequence { step: Some(0), .. } => {
+				error(String::from("sequence step must not be 0"))
+			}
+			DataType::Sequence { .. } => {}
+			DataType::ForeignKey {
+				model,
+				property,
+				selection,
+			} => match self.get_definition(model) {
+				Some(target) => {
+					if !target.properties.contains_key(property) {
+						error(format!("model `{}` has no property `{}`", model, property));
+					}
+					if let Some(Selection::Weighted { property: weight }) = selection {
+						if !target.properties.contains_key(weight) {
+							error(format!("model `{}` has no property `{}`", model, weight));
+						}
+					}
+				}
+				None => error(format!("unknown model `{}`", model)),
+			},
+			DataType::Reference { path, property } => {
+				let parts: Vec<&str> = path.split('~').collect();
+				if parts.iter().any(|part| *part != "^") {
+					error(format!(
+						"invalid reference path `{}`, expected a chain of `^` separated by `~`",
+						path
+					));
+					return;
+				}
+
+				let ancestors = self.ancestors_at_depth(model_name, parts.len());
+				if ancestors.is_empty() {
+					error(format!(
+						"reference path `{}` does not reach an ancestor of `{}`",
+						path, model_name
+					));
+				}
+				for ancestor in ancestors {
+					if !self.models[&ancestor].properties.contains_key(property) {
+						error(format!(
+							"reference path `{}` reaches `{}`, which has no property `{}`",
+							path, ancestor, property
+						));
+					}
+				}
+			}
+		}
+	}
+
+	/// Find every model that contains the given model through a `Model` or `List` property
+	fn parents_of(&self, model_name: &str) -> Vec<String> {
+		let mut parents: Vec<String> = self
+			.models
+			.iter()
+			.filter(|(_, model)| {
+				model
+					.type_iter()
+					.any(|(_, data_type)| match data_type.element_type() {
+						DataType::Model(child) => child.name() == model_name,
+						_ => false,
+					})
+			})
+			.map(|(name, _)| name.clone())
+			.collect();
+		parents.sort();
+		parents
+	}
+
+	/// Find every model that can be reached by following `depth` parent steps up from the given
+	/// model, as a `^~^` style reference path would
+	fn ancestors_at_depth(&self, model_name: &str, depth: usize) -> Vec<String> {
+		let mut current = vec![model_name.to_string()];
+		for _ in 0..depth {
+			let mut next: Vec<String> = current
+				.iter()
+				.flat_map(|name| self.parents_of(name))
+				.collect();
+			next.sort();
+			next.dedup();
+			current = next;
+		}
+		current
+	}
+
+	/// The columns that should be written for the given model in tabular output. This is the
+	/// `serialize` ordering where one is given, and otherwise every property that holds a value,
+	/// in the order the properties were written in the specification
+	pub fn column_order<S: ToString>(&self, name: S) -> Vec<String> {
+		let name = name.to_string();
+		if let Some(order) = self.serialize.get(&name) {
+			return order.clone();
+		}
+
+		match self.models.get(&name) {
+			Some(model) => model
+				.declaration_order()
+				.iter()
+				.filter(|property| {
+					match model.properties.get(*property).map(DataType::element_type) {
+						Some(DataType::Model(_)) => false,
+						_ => true,
+					}
+				})
+				.cloned()
+				.collect(),
+			None => Vec::new(),
+		}
+	}
 }
 
 pub mod io {
-	use super::Specification;
+	use super::{first_position_only, RunPlan, Specification};
 	use crate::generator::from_spec;
 
 	use failure::Fail;
+	use lazy_static::lazy_static;
+	use regex::Regex;
 	use std::convert::AsRef;
+	use std::fmt;
 	use std::fs::read_to_string;
 	use std::io::Result;
 	use std::path::{Path, PathBuf};
 
+	use serde::de::DeserializeOwned;
 	use serde_derive::{Deserialize, Serialize};
-	use serde_json::{error::Category, from_str};
+	use serde_json::error::Category;
 
 	pub type SpecResult<Success> = std::result::Result<Success, SpecError>;
 
+	/// The file formats that a specification or run plan can be written in
+	#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+	pub enum SpecFormat {
+		JSON,
+		YAML,
+		TOML,
+	}
+
+	impl SpecFormat {
+		/// Pick the format from the extension of the given path. Files without a `.yaml`, `.yml`
+		/// or `.toml` extension are read as JSON
+		pub fn from_path<P: AsRef<Path>>(path: P) -> SpecFormat {
+			match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+				Some("yaml") | Some("yml") => SpecFormat::YAML,
+				Some("toml") => SpecFormat::TOML,
+				_ => SpecFormat::JSON,
+			}
+		}
+	}
+
+	impl fmt::Display for SpecFormat {
+		fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+			write!(f, "{:?}", self)
+		}
+	}
+
 	#[derive(Debug, Fail)]
 	pub enum SpecError {
 		#[fail(display = "Could not find the spec file: {}", 0)]
@@ -74,10 +1884,15 @@ pub mod io {
 		IOError { inner: std::io::Error },
 		#[fail(display = "The provided spec path could not be correctly converted")]
 		BadPath,
-		#[fail(display = "The specification is not valid JSON")]
-		BadFormat,
-		#[fail(display = "The specification contained one or more invalid definitions")]
-		BadData,
+		#[fail(display = "The specification is not valid {}: {}", format, reason)]
+		BadFormat { format: SpecFormat, reason: String },
+		#[fail(
+			display = "The specification contained an invalid definition: {}",
+			reason
+		)]
+		BadData { reason: String },
+		#[fail(display = "Could not include {}: {}", path, reason)]
+		BadInclude { path: String, reason: String },
 	}
 
 	fn pathable_to_string<P: AsRef<Path>>(path: &P) -> SpecResult<String> {
@@ -87,24 +1902,520 @@ pub mod io {
 		};
 	}
 
+	/// Read a `Specification` from a file, in the format given by its extension
 	pub fn read_spec<P: AsRef<Path>>(path: P) -> SpecResult<Specification> {
-		let content = match read_to_string(&path) {
-			Ok(content) => content,
+		let format = SpecFormat::from_path(&path);
+		read_spec_as(path, format)
+	}
+
+	/// Read a `Specification` from a file in the given format, whatever its extension. The spec
+	/// files it includes are read in the formats given by their own extensions
+	pub fn read_spec_as<P: AsRef<Path>>(path: P, format: SpecFormat) -> SpecResult<Specification> {
+		read_with_includes(path.as_ref(), format, &mut Vec::new())
+	}
+
+	/// Read a spec file along with every spec file it includes. `including` holds the files that
+	/// are part way through being read, so that a file that includes itself can be rejected
+	fn read_with_includes(
+		path: &Path,
+		format: SpecFormat,
+		including: &mut Vec<PathBuf>,
+	) -> SpecResult<Specification> {
+		let mut spec: Specification = parse(&read_file(path)?, format)?;
+		let canonical = path
+			.canonicalize()
+			.map_err(|inner| SpecError::IOError { inner })?;
+		spec.set_source(&canonical);
+		let includes = spec.take_includes();
+		if includes.is_empty() {
+			return resolve(spec);
+		}
+
+		including.push(canonical);
+		let folder = path.parent().unwrap_or_else(|| Path::new(""));
+		for include in includes {
+			let include_path = folder.join(&include.path);
+			let bad_include = |reason: String| SpecError::BadInclude {
+				path: include.path.clone(),
+				reason,
+			};
+
+			if let Ok(canonical) = include_path.canonicalize() {
+				if including.contains(&canonical) {
+					return Err(bad_include(String::from("the spec includes itself")));
+				}
+			}
+			let included = read_with_includes(
+				&include_path,
+				SpecFormat::from_path(&include_path),
+				including,
+			)?;
+			spec.merge(included, include.namespace.as_ref().map(String::as_str))
+				.map_err(bad_include)?;
+		}
+		including.pop();
+
+		resolve(spec)
+	}
+
+	fn resolve(mut spec: Specification) -> SpecResult<Specification> {
+		spec.resolve_extends()
+			.map_err(|reason| SpecError::BadData { reason })?;
+		Ok(spec)
+	}
+
+	/// Read a `Specification` from the contents of a spec file. Any spec files that it includes
+	/// are not read, as there is no file for their paths to be relative to
+	pub fn parse_spec(content: &str, format: SpecFormat) -> SpecResult<Specification> {
+		resolve(parse(content, format)?)
+	}
+
+	/// Read a `RunPlan` from a file, in the format given by its extension. The plan is laid out in
+	/// the same way as the deprecated `GenerationSpecification`
+	pub fn read_plan<P: AsRef<Path>>(path: P) -> SpecResult<RunPlan> {
+		let format = SpecFormat::from_path(&path);
+		parse(&read_file(path)?, format)
+	}
+
+	fn read_file<P: AsRef<Path>>(path: P) -> SpecResult<String> {
+		match read_to_string(&path) {
+			Ok(content) => Ok(content),
 			Err(e) => match e.kind() {
 				std::io::ErrorKind::NotFound => {
-					return Err(SpecError::MissingFile(pathable_to_string(&path)?))
+					Err(SpecError::MissingFile(pathable_to_string(&path)?))
 				}
-				_ => return Err(SpecError::IOError { inner: e }),
+				_ => Err(SpecError::IOError { inner: e }),
 			},
-		};
+		}
+	}
 
-		match from_str(&content) {
-			Ok(spec) => Ok(spec),
-			Err(e) => match e.classify() {
-				Category::Eof | Category::Syntax => Err(SpecError::BadFormat),
-				Category::Data => Err(SpecError::BadData),
-				Category::Io => Err(SpecError::IOError { inner: e.into() }),
+	fn parse<T: DeserializeOwned>(content: &str, format: SpecFormat) -> SpecResult<T> {
+		match format {
+			SpecFormat::JSON => match serde_json::from_str(content) {
+				Ok(spec) => Ok(spec),
+				Err(e) => match e.classify() {
+					Category::Eof | Category::Syntax => Err(SpecError::BadFormat {
+						format,
+						reason: first_position_only(&e.to_string()),
+					}),
+					Category::Data => Err(SpecError::BadData {
+						reason: first_position_only(&e.to_string()),
+					}),
+					Category::Io => Err(SpecError::IOError { inner: e.into() }),
+				},
 			},
+			// Neither YAML nor TOML errors say whether the file could be read at all, so a failing
+			// file is read again without a schema to tell the two apart
+			SpecFormat::YAML => serde_yaml::from_str(content).map_err(|e| {
+				let readable = serde_yaml::from_str::<serde_yaml::Value>(content).is_ok();
+				content_error(format, readable, e.to_string())
+			}),
+			SpecFormat::TOML => toml::from_str(content).map_err(|e| {
+				let readable = toml::from_str::<toml::Value>(content).is_ok();
+				content_error(format, readable, e.to_string())
+			}),
+		}
+	}
+
+	lazy_static! {
+		/// YAML errors name the path to the value at each level, such as `models.user.id: `
+		static ref YAML_PATH: Regex = Regex::new(r"(^|: )[\w.\[\]]+: ").unwrap();
+		/// TOML errors name the key at each level, such as ` for key `models``
+		static ref TOML_KEY: Regex = Regex::new(r" for key `[^`]*`").unwrap();
+	}
+
+	/// Build the error for a YAML or TOML file that could not be read. The paths that these
+	/// formats add are removed, as the message already names the model and property
+	fn content_error(format: SpecFormat, readable: bool, message: String) -> SpecError {
+		let message = match format {
+			SpecFormat::YAML => YAML_PATH.replace_all(&message, "$1").to_string(),
+			SpecFormat::TOML => TOML_KEY.replace_all(&message, "").to_string(),
+			SpecFormat::JSON => message,
+		};
+		let reason = first_position_only(&message);
+		if readable {
+			SpecError::BadData { reason }
+		} else {
+			SpecError::BadFormat { format, reason }
+		}
+	}
+}
+
+#[test]
+fn list_lengths_default_and_bounds() {
+	let mut rng = rand::thread_rng();
+	let unsized_list: DataType =
+		serde_json::from_str(r#"{ "type": "List", "value": { "type": "Model", "value": "a" } }"#)
+			.unwrap();
+	let sized_list: DataType = serde_json::from_str(
+		r#"{ "type": "List", "value": { "of": { "type": "Model", "value": "a" }, "min": 1, "max": 3 } }"#,
+	)
+	.unwrap();
+
+	match (unsized_list, sized_list) {
+		(DataType::List(unsized_list), DataType::List(sized_list)) => {
+			assert_eq!(unsized_list.length(&mut rng), DEFAULT_LIST_LENGTH);
+			for _ in 0..20 {
+				let length = sized_list.length(&mut rng);
+				assert!(length >= 1 && length <= 3);
+			}
+		}
+		_ => panic!("Expected list definitions"),
+	}
+}
+
+#[test]
+fn shorthand_matches_the_full_definition() {
+	let pairs = vec![
+		(
+			r#""UUID4""#,
+			r#"{ "type": "RandomData", "value": "UUID4" }"#,
+		),
+		(
+			r#"{ "NumberBetween": { "min": 1, "max": 10 } }"#,
+			r#"{ "type": "RandomData", "value": { "NumberBetween": { "min": 1, "max": 10 } } }"#,
+		),
+		(r#""@user""#, r#"{ "type": "Model", "value": "user" }"#),
+		(
+			r#"["@template"]"#,
+			r#"{ "type": "List", "value": { "type": "Model", "value": "template" } }"#,
+		),
+		(
+			r#""^~^.id""#,
+			r#"{ "type": "Reference", "value": { "path": "^~^", "property": "id" } }"#,
+		),
+		(
+			r#"{ "value": "UUID4", "type": "RandomData" }"#,
+			r#"{ "type": "RandomData", "value": "UUID4" }"#,
+		),
+	];
+	for (shorthand, full) in pairs {
+		let shorthand: DataType = serde_json::from_str(shorthand).unwrap();
+		let full: DataType = serde_json::from_str(full).unwrap();
+		assert_eq!(
+			serde_json::to_value(shorthand).unwrap(),
+			serde_json::to_value(full).unwrap()
+		);
+	}
+
+	assert!(serde_json::from_str::<DataType>(r#""^""#).is_err());
+	assert!(serde_json::from_str::<DataType>(r#"["Email", "Email"]"#).is_err());
+}
+
+#[test]
+fn merged_specs_are_namespaced_and_conflicts_rejected() {
+	let mut spec: Specification =
+		serde_json::from_str(r#"{ "models": { "post": { "author": "@common::user" } } }"#).unwrap();
+	let common = r#"{
+		"serialize": { "user": ["id"] },
+		"models": {
+			"user": { "id": "UUID4", "home": "@address" },
+			"address": { "city": "City" }
+		}
+	}"#;
+
+	spec.merge(serde_json::from_str(common).unwrap(), Some("common"))
+		.unwrap();
+	assert!(spec.has_model("common::user"));
+	assert!(spec.get_serialize_ref("common::user").is_some());
+	assert!(spec.validate().is_ok());
+
+	let conflict = spec
+		.merge(serde_json::from_str(common).unwrap(), Some("common"))
+		.unwrap_err();
+	assert!(conflict.contains("model `common::user` is already defined"));
+
+	// The same file included twice into the same namespace is only added once
+	let mut library: Specification = serde_json::from_str(common).unwrap();
+	library.set_source(Path::new("/specs/common.json"));
+	let mut spec: Specification = serde_json::from_str(r#"{ "models": {} }"#).unwrap();
+	spec.merge(library.clone(), Some("common")).unwrap();
+	spec.merge(library.clone(), Some("common")).unwrap();
+	spec.merge(library, Some("shared")).unwrap();
+	assert!(spec.has_model("shared::user"));
+
+	let french = serde_json::from_str(r#"{ "locale": "fr_FR", "models": {} }"#).unwrap();
+	assert!(spec.merge(french, None).is_err());
+}
+
+#[test]
+fn models_inherit_from_the_models_they_extend() {
+	use self::io::{parse_spec, SpecFormat};
+
+	let spec = parse_spec(
+		r#"{
+			"serialize": { "base": ["id"], "timestamps": ["created_at"], "post": ["title"] },
+			"models": {
+				"post": {
+					"extends": ["base", "timestamps"],
+					"title": "Sentence",
+					"id": { "type": "RandomData", "value": { "Number": { "digits": 5 } } }
+				},
+				"base": { "id": "UUID4" },
+				"timestamps": { "created_at": { "type": "RandomData", "value": { "DateTime": {} } } }
+			}
+		}"#,
+		SpecFormat::JSON,
+	)
+	.unwrap();
+
+	let post = spec.get_definition("post").unwrap();
+	assert_eq!(post.declaration_order(), &vec!["id", "created_at", "title"]);
+	match post.type_iter().find(|(name, _)| *name == "id") {
+		Some((_, DataType::RandomData(RandomData::Number { .. }))) => {}
+		other => panic!("expected the overriding id, got {:?}", other),
+	}
+	assert_eq!(spec.column_order("post"), vec!["id", "created_at", "title"]);
+
+	// A model without a `serialize` entry of its own writes its own properties too
+	let spec = parse_spec(
+		r#"{
+			"serialize": { "timestamps": ["created_at"] },
+			"models": {
+				"post": { "extends": ["timestamps"], "id": "UUID4", "title": "Sentence" },
+				"timestamps": { "created_at": { "type": "RandomData", "value": { "DateTime": {} } } }
+			}
+		}"#,
+		SpecFormat::JSON,
+	)
+	.unwrap();
+	assert_eq!(spec.column_order("post"), vec!["created_at", "id", "title"]);
+
+	let cycle = parse_spec(
+		r#"{ "models": { "a": { "extends": ["b"] }, "b": { "extends": ["a"] } } }"#,
+		SpecFormat::JSON,
+	);
+	assert!(cycle.is_err());
+
+	let unresolved: Specification = serde_json::from_str(
+		r#"{ "models": { "post": { "extends": ["base"] }, "base": { "id": "UUID4" } } }"#,
+	)
+	.unwrap();
+	assert!(unresolved.validate().is_err());
+	let data = crate::generator::from_spec(String::from("post"), unresolved, 1, None).unwrap();
+	assert!(data["post"][0].contains_key("id"));
+
+	// `extends` and `unique` are only model settings when they are lists
+	let spec = parse_spec(
+		r#"{
+			"models": {
+				"coupon": {
+					"extends": { "type": "RandomData", "value": { "Number": { "digits": 2 } } },
+					"unique": "UUID4",
+					"code": "UUID4"
+				}
+			}
+		}"#,
+		SpecFormat::JSON,
+	)
+	.unwrap();
+	let coupon = spec.get_definition("coupon").unwrap();
+	assert_eq!(
+		coupon.declaration_order(),
+		&vec!["extends", "unique", "code"]
+	);
+	assert!(coupon.unique_keys().is_empty());
+}
+
+#[test]
+fn validation_reports_every_problem() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"serialize": { "user": ["id", "email"], "ghost": ["id"] },
+			"models": {
+				"user": {
+					"id": { "type": "RandomData", "value": { "Number": { "digits": 0 } } },
+					"age": { "type": "RandomData", "value": { "NumberBetween": { "min": 5, "max": 5 } } },
+					"posts": { "type": "List", "value": { "type": "Model", "value": "post" } },
+					"friend": { "type": "Model", "value": "friend" },
+					"letters": { "type": "List", "value": { "type": "Model", "value": "post" }, "locale": "fr_FR" }
+				},
+				"post": {
+					"user_id": { "type": "Reference", "value": { "path": "^", "property": "id" } },
+					"site_id": { "type": "Reference", "value": { "path": "^~^", "property": "id" } }
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let locations: Vec<String> = spec
+		.validate()
+		.unwrap_err()
+		.into_iter()
+		.map(|error| error.location)
+		.collect();
+	assert_eq!(
+		locations,
+		vec![
+			"models.post.site_id",
+			"models.user.id",
+			"models.user.age",
+			"models.user.friend",
+			"models.user.letters",
+			"serialize.ghost",
+			"serialize.user",
+		]
+	);
+}
+
+#[test]
+fn templates_are_checked_for_unknown_fields_and_cycles() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"models": {
+				"user": {
+					"a": "{b}-{c}",
+					"b": "{a}",
+					"c": "{Nope}",
+					"d": "{unclosed",
+					"e": "{child}:{^.id}",
+					"f": "{n:wide}",
+					"child": "@user"
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let errors = spec.validate().unwrap_err();
+	let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+	assert!(messages.contains(&String::from(
+		"models.user.c: template field `Nope` is not a value, a property, a `^` reference, `#` or a RandomData type"
+	)));
+	assert!(messages.contains(&String::from(
+		"models.user.d: unclosed `{` in template `{unclosed`"
+	)));
+	assert!(messages.contains(&String::from(
+		"models.user.e: template field `child` is a child model, which can not be written as text"
+	)));
+	assert!(messages.contains(&String::from(
+		"models.user.e: reference path `^` reaches `user`, which has no property `id`"
+	)));
+	assert!(messages.contains(&String::from(
+		"models.user.a: template uses its own value through `a -> b -> a`"
+	)));
+	assert!(messages.contains(&String::from(
+		"models.user.b: template uses its own value through `b -> a -> b`"
+	)));
+	assert!(messages
+		.iter()
+		.any(|message| message.starts_with("models.user.f: invalid width `wide`")));
+}
+
+#[test]
+fn cycles_need_a_max_depth() {
+	let spec: Specification = serde_json::from_str(
+		r#"{
+			"serialize": {},
+			"models": {
+				"a": { "b": { "type": "Model", "value": "b" } },
+				"b": { "a": { "type": "List", "value": { "type": "Model", "value": "a" } } },
+				"comment": {
+					"replies": {
+						"type": "List",
+						"value": { "type": "Model", "value": { "model": "comment", "max_depth": 3 } }
+					}
+				}
+			}
+		}"#,
+	)
+	.unwrap();
+
+	let errors = spec.validate().unwrap_err();
+	assert_eq!(errors.len(), 1);
+	assert_eq!(errors[0].location, "models.b.a");
+	assert!(errors[0].message.contains("`a.b -> b.a`"));
+}
+
+#[test]
+fn parse_errors_name_the_model_and_property() {
+	let error = serde_json::from_str::<Specification>(
+		r#"{
+			"serialize": {},
+			"models": {
+				"user": {
+					"id": { "type": "RandomData", "value": "UUID5" }
+				}
+			}
+		}"#,
+	)
+	.unwrap_err();
+	let message = first_position_only(&error.to_string());
+	assert!(message.starts_with("model `user`: property `id`: unknown variant `UUID5`"));
+	assert!(message.ends_with("at line 5 column 51"));
+
+	let error = serde_json::from_str::<Specification>(
+		r#"{
+			"models": {
+				"shelf": {
+					"books": { "type": "List", "value": { "of": "Sentence", "cout": 3 } }
+				}
+			}
+		}"#,
+	)
+	.unwrap_err();
+	assert!(error.to_string().contains("unknown field `cout`"));
+	let error = serde_json::from_str::<Specification>(
+		r#"{ "models": { "shelf": { "title": { "type": "Template", "value": { "template": "{isbn}", "values": { "isbn": "UUID5" } } } } } }"#,
+	)
+	.unwrap_err();
+	assert!(error.to_string().contains("unknown variant `UUID5`"));
+}
+
+#[test]
+fn specs_can_be_written_in_yaml_and_toml() {
+	use self::io::{parse_spec, SpecError, SpecFormat};
+
+	let yaml = parse_spec(
+		r#"
+serialize: {}
+models:
+  user:
+    # Comments are allowed in YAML
+    id: { type: RandomData, value: UUID4 }
+    age:
+      type: RandomData
+      value: { NumberBetween: { min: 18, max: 65 } }
+"#,
+		SpecFormat::YAML,
+	)
+	.unwrap();
+	assert_eq!(yaml.column_order("user"), vec!["id", "age"]);
+
+	let toml = parse_spec(
+		r#"
+[serialize]
+
+[models.user]
+id = { type = "RandomData", value = "UUID4" }
+age = { type = "RandomData", value = { NumberBetween = { min = 18, max = 65 } } }
+"#,
+		SpecFormat::TOML,
+	)
+	.unwrap();
+	assert_eq!(toml.column_order("user"), vec!["id", "age"]);
+
+	match parse_spec("models: [", SpecFormat::YAML) {
+		Err(SpecError::BadFormat { format, .. }) => assert_eq!(format, SpecFormat::YAML),
+		other => panic!("expected a format error, got {:?}", other),
+	}
+	match parse_spec(
+		"serialize: {}\nmodels:\n  user:\n    id: { type: RandomData, value: UUID5 }",
+		SpecFormat::YAML,
+	) {
+		Err(SpecError::BadData { reason }) => {
+			assert!(reason.starts_with("model `user`: property `id`: unknown variant `UUID5`"))
+		}
+		other => panic!("expected a data error, got {:?}", other),
+	}
+	match parse_spec(
+		"[serialize]\n[models.user]\nid = { type = \"RandomData\", value = \"UUID5\" }",
+		SpecFormat::TOML,
+	) {
+		Err(SpecError::BadData { reason }) => {
+			assert!(reason.starts_with("model `user`: property `id`: unknown variant `UUID5`"))
 		}
+		other => panic!("expected a data error, got {:?}", other),
 	}
 }